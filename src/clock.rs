@@ -0,0 +1,476 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::game::{Color, Game, GamePhase, GameResult};
+use crate::state::AppState;
+use crate::ws;
+
+/// How often remaining time is pushed to clients
+const TICK: Duration = Duration::from_secs(1);
+
+/// Time settings for a game (all times in seconds)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimeControl {
+    /// A fixed amount of time for the whole game
+    Absolute { main_time: u64 },
+    /// Main time plus `increment` after every move, optionally capped at `max_time`
+    Fischer {
+        main_time: u64,
+        increment: u64,
+        #[serde(default)]
+        max_time: Option<u64>,
+    },
+    /// Main time, then `periods` periods of `period_time`; a period is only used up by overrunning it
+    ByoYomi { main_time: u64, period_time: u64, periods: u32 },
+    /// Main time, then `stones` moves to be played in every `period_time`
+    Canadian { main_time: u64, period_time: u64, stones: u32 },
+}
+
+impl TimeControl {
+    /// Reject settings the clock cannot run with (empty overtime periods)
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            TimeControl::Absolute { .. } | TimeControl::Fischer { .. } => Ok(()),
+            TimeControl::ByoYomi { period_time, periods, .. } => {
+                if period_time == 0 || periods == 0 {
+                    return Err("Byo-yomi needs a period time and at least one period".to_string());
+                }
+                Ok(())
+            }
+            TimeControl::Canadian { period_time, stones, .. } => {
+                if period_time == 0 || stones == 0 {
+                    return Err("Canadian overtime needs a period time and at least one stone".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// One player's remaining time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PlayerClock {
+    main_time: Duration,
+    period_time: Duration, // Left in the current overtime period
+    periods: u32,          // Byo-yomi periods left, including the current one
+    stones: u32,           // Canadian moves still due in the current period
+}
+
+/// Remaining time as sent to clients
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PlayerTime {
+    main_ms: u64,
+    period_ms: u64,
+    periods: u32,
+    stones: u32,
+}
+
+impl From<PlayerClock> for PlayerTime {
+    fn from(clock: PlayerClock) -> Self {
+        Self {
+            main_ms: clock.main_time.as_millis() as u64,
+            period_ms: clock.period_time.as_millis() as u64,
+            periods: clock.periods,
+            stones: clock.stones,
+        }
+    }
+}
+
+/// Both clocks at a moment in time
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ClockState {
+    control: TimeControl,
+    black: PlayerTime,
+    white: PlayerTime,
+    running: Option<Color>,
+}
+
+/// Game clock for both players
+///
+/// Counting starts with `start` and from then on follows the game: it runs for the
+/// player to move and stops while the game is being scored or once it is over.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    control: TimeControl,
+    clocks: (PlayerClock, PlayerClock), // (black, white)
+    running: Option<(Color, Instant)>,  // Player thinking, and since when
+    started: bool,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        let secs = Duration::from_secs;
+        let clock = match control {
+            TimeControl::Absolute { main_time } | TimeControl::Fischer { main_time, .. } => PlayerClock {
+                main_time: secs(main_time),
+                period_time: Duration::ZERO,
+                periods: 0,
+                stones: 0,
+            },
+            TimeControl::ByoYomi { main_time, period_time, periods } => PlayerClock {
+                main_time: secs(main_time),
+                period_time: secs(period_time),
+                periods,
+                stones: 0,
+            },
+            TimeControl::Canadian { main_time, period_time, stones } => PlayerClock {
+                main_time: secs(main_time),
+                period_time: secs(period_time),
+                periods: 0,
+                stones,
+            },
+        };
+
+        Self {
+            control,
+            clocks: (clock, clock),
+            running: None,
+            started: false,
+        }
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Start counting for the player to move
+    pub fn start(&mut self, game: &Game, now: Instant) {
+        self.started = true;
+        self.follow(game, now);
+    }
+
+    /// Catch up with the game after a move, pass or phase change
+    ///
+    /// Charges the player whose turn just ended and starts the player to move.
+    /// Returns the player who ran out of time, if the move came too late.
+    pub fn follow(&mut self, game: &Game, now: Instant) -> Option<Color> {
+        if !self.started {
+            return None;
+        }
+
+        let to_move = (game.get_phase() == GamePhase::Playing).then(|| game.get_turn());
+        if let Some((color, since)) = self.running {
+            if to_move == Some(color) {
+                return None; // Still thinking (e.g. placing free handicap stones)
+            }
+
+            match spend(&self.control, self.clock(color), now.saturating_duration_since(since)) {
+                Some(mut after) => {
+                    finish_move(&self.control, &mut after);
+                    *self.clock_mut(color) = after;
+                }
+                None => {
+                    self.flag(color);
+                    return Some(color);
+                }
+            }
+        }
+
+        self.running = to_move.map(|color| (color, now));
+        None
+    }
+
+    /// The player to move, if their time ran out by `now`
+    pub fn check(&mut self, now: Instant) -> Option<Color> {
+        let (color, since) = self.running?;
+        if spend(&self.control, self.clock(color), now.saturating_duration_since(since)).is_none() {
+            self.flag(color);
+            return Some(color);
+        }
+        None
+    }
+
+    /// When the player to move runs out of time
+    pub fn deadline(&self) -> Option<Instant> {
+        let (color, since) = self.running?;
+        let clock = self.clock(color);
+        let overtime = match self.control {
+            TimeControl::Absolute { .. } | TimeControl::Fischer { .. } => Duration::ZERO,
+            TimeControl::ByoYomi { .. } => clock.period_time * clock.periods,
+            TimeControl::Canadian { .. } => clock.period_time,
+        };
+        Some(since + clock.main_time + overtime)
+    }
+
+    /// Remaining time for both players as of `now`
+    pub fn state(&self, now: Instant) -> ClockState {
+        let view = |color: Color| {
+            let clock = *self.clock(color);
+            let remaining = match self.running {
+                Some((running, since)) if running == color => {
+                    spend(&self.control, &clock, now.saturating_duration_since(since)).unwrap_or(PlayerClock {
+                        main_time: Duration::ZERO,
+                        period_time: Duration::ZERO,
+                        periods: 0,
+                        ..clock
+                    })
+                }
+                _ => clock,
+            };
+            PlayerTime::from(remaining)
+        };
+
+        ClockState {
+            control: self.control,
+            black: view(Color::Black),
+            white: view(Color::White),
+            running: self.running.map(|(color, _)| color),
+        }
+    }
+
+    /// Stop the clock with `color` out of time
+    fn flag(&mut self, color: Color) {
+        let clock = self.clock_mut(color);
+        clock.main_time = Duration::ZERO;
+        clock.period_time = Duration::ZERO;
+        clock.periods = 0;
+        self.running = None;
+    }
+
+    fn clock(&self, color: Color) -> &PlayerClock {
+        match color {
+            Color::Black => &self.clocks.0,
+            Color::White => &self.clocks.1,
+        }
+    }
+
+    fn clock_mut(&mut self, color: Color) -> &mut PlayerClock {
+        match color {
+            Color::Black => &mut self.clocks.0,
+            Color::White => &mut self.clocks.1,
+        }
+    }
+}
+
+/// A player's clock after `elapsed` of thinking, or None if that ran out their time
+fn spend(control: &TimeControl, clock: &PlayerClock, elapsed: Duration) -> Option<PlayerClock> {
+    let mut after = *clock;
+    if elapsed < clock.main_time {
+        after.main_time -= elapsed;
+        return Some(after);
+    }
+
+    let over = elapsed - clock.main_time;
+    after.main_time = Duration::ZERO;
+
+    match *control {
+        TimeControl::Absolute { .. } | TimeControl::Fischer { .. } => None,
+        TimeControl::ByoYomi { period_time, .. } => {
+            // Every full period overrun is gone; the move has to land inside the next one
+            let period_time = Duration::from_secs(period_time);
+            let used = (over.as_nanos() / period_time.as_nanos()) as u32;
+            if used >= clock.periods {
+                return None;
+            }
+            after.periods -= used;
+            after.period_time = period_time - (over - period_time * used);
+            Some(after)
+        }
+        TimeControl::Canadian { .. } => {
+            if over >= clock.period_time {
+                return None;
+            }
+            after.period_time -= over;
+            Some(after)
+        }
+    }
+}
+
+/// Apply what a completed move earns: increment, a fresh period or a counted stone
+fn finish_move(control: &TimeControl, clock: &mut PlayerClock) {
+    match *control {
+        TimeControl::Absolute { .. } => {}
+        TimeControl::Fischer { increment, max_time, .. } => {
+            clock.main_time += Duration::from_secs(increment);
+            if let Some(max_time) = max_time {
+                clock.main_time = clock.main_time.min(Duration::from_secs(max_time));
+            }
+        }
+        TimeControl::ByoYomi { period_time, .. } => {
+            if clock.main_time.is_zero() {
+                clock.period_time = Duration::from_secs(period_time);
+            }
+        }
+        TimeControl::Canadian { period_time, stones, .. } => {
+            if clock.main_time.is_zero() {
+                clock.stones -= 1;
+                if clock.stones == 0 {
+                    clock.stones = stones;
+                    clock.period_time = Duration::from_secs(period_time);
+                }
+            }
+        }
+    }
+}
+
+/// Background task: ends games on time and pushes clock ticks to clients
+pub async fn run(state: Arc<AppState>) {
+    let mut ticks = tokio::time::interval(TICK);
+
+    loop {
+        let deadline = state.clock.lock().await.as_ref().and_then(Clock::deadline);
+
+        // Wake on the next tick, or exactly when the player to move runs out of time
+        match deadline {
+            Some(deadline) => tokio::select! {
+                _ = ticks.tick() => {}
+                _ = tokio::time::sleep_until(deadline.into()) => {}
+            },
+            None => {
+                ticks.tick().await;
+            }
+        }
+
+        let mut game = state.game.lock().await;
+        let mut clock = state.clock.lock().await;
+        let Some(game_clock) = clock.as_mut() else {
+            continue;
+        };
+
+        let now = Instant::now();
+        if let Some(loser) = game_clock.check(now) {
+            game.end(GameResult::Time { winner: loser.opposite() });
+            drop(clock);
+            drop(game);
+            ws::broadcast_state(&state).await;
+        } else if game_clock.running.is_some() {
+            let clock_state = game_clock.state(now);
+            drop(clock);
+            drop(game);
+            ws::broadcast_clock(&state, clock_state).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Position;
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    /// A started clock and a game with Black to move
+    fn started(control: TimeControl, t0: Instant) -> (Clock, Game) {
+        let game = Game::new();
+        let mut clock = Clock::new(control);
+        clock.start(&game, t0);
+        (clock, game)
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_overtime() {
+        let invalid = [
+            TimeControl::ByoYomi { main_time: 60, period_time: 0, periods: 3 },
+            TimeControl::ByoYomi { main_time: 60, period_time: 30, periods: 0 },
+            TimeControl::Canadian { main_time: 60, period_time: 0, stones: 10 },
+            TimeControl::Canadian { main_time: 60, period_time: 300, stones: 0 },
+        ];
+        for control in invalid {
+            assert!(control.validate().is_err(), "{:?}", control);
+        }
+
+        assert!(TimeControl::ByoYomi { main_time: 0, period_time: 30, periods: 1 }.validate().is_ok());
+        assert!(TimeControl::Canadian { main_time: 0, period_time: 300, stones: 1 }.validate().is_ok());
+        assert!(TimeControl::Absolute { main_time: 0 }.validate().is_ok());
+    }
+
+    #[test]
+    fn test_absolute_charges_mover_and_flags() {
+        let t0 = Instant::now();
+        let (mut clock, mut game) = started(TimeControl::Absolute { main_time: 60 }, t0);
+        assert_eq!(clock.deadline(), Some(t0 + secs(60)));
+
+        game.place_stone(Position::new(3, 3), Color::Black).unwrap();
+        assert_eq!(clock.follow(&game, t0 + secs(10)), None);
+        assert_eq!(clock.clock(Color::Black).main_time, secs(50));
+        assert_eq!(clock.deadline(), Some(t0 + secs(70))); // White's full minute
+
+        assert_eq!(clock.check(t0 + secs(69)), None);
+        assert_eq!(clock.check(t0 + secs(70)), Some(Color::White));
+        assert_eq!(clock.deadline(), None);
+    }
+
+    #[test]
+    fn test_fischer_increment_is_capped() {
+        let t0 = Instant::now();
+        let control = TimeControl::Fischer { main_time: 30, increment: 10, max_time: Some(35) };
+        let (mut clock, mut game) = started(control, t0);
+
+        game.place_stone(Position::new(3, 3), Color::Black).unwrap();
+        clock.follow(&game, t0 + secs(2));
+        assert_eq!(clock.clock(Color::Black).main_time, secs(35));
+
+        game.place_stone(Position::new(4, 4), Color::White).unwrap();
+        clock.follow(&game, t0 + secs(22));
+        assert_eq!(clock.clock(Color::White).main_time, secs(20));
+    }
+
+    #[test]
+    fn test_byo_yomi_uses_up_overrun_periods() {
+        let t0 = Instant::now();
+        let control = TimeControl::ByoYomi { main_time: 10, period_time: 30, periods: 3 };
+        let (mut clock, mut game) = started(control, t0);
+        assert_eq!(clock.deadline(), Some(t0 + secs(100)));
+
+        // 10s main time plus 75s overtime: two periods gone, the third resets after the move
+        game.place_stone(Position::new(3, 3), Color::Black).unwrap();
+        clock.follow(&game, t0 + secs(85));
+        let black = clock.clock(Color::Black);
+        assert_eq!((black.main_time, black.periods, black.period_time), (Duration::ZERO, 1, secs(30)));
+
+        game.place_stone(Position::new(4, 4), Color::White).unwrap();
+        clock.follow(&game, t0 + secs(90));
+        assert_eq!(clock.deadline(), Some(t0 + secs(120)));
+        assert_eq!(clock.check(t0 + secs(120)), Some(Color::Black));
+    }
+
+    #[test]
+    fn test_canadian_period_resets_after_enough_stones() {
+        let t0 = Instant::now();
+        let control = TimeControl::Canadian { main_time: 0, period_time: 60, stones: 2 };
+        let (mut clock, mut game) = started(control, t0);
+        let mut now = t0;
+
+        let moves = [(3, 3), (15, 15), (3, 15), (15, 3)];
+        for (i, &(x, y)) in moves.iter().enumerate() {
+            game.place_stone(Position::new(x, y), game.get_turn()).unwrap();
+            now += secs(20);
+            clock.follow(&game, now);
+
+            let black = clock.clock(Color::Black);
+            if i == 0 {
+                assert_eq!((black.period_time, black.stones), (secs(40), 1));
+            }
+        }
+
+        let black = clock.clock(Color::Black);
+        assert_eq!((black.period_time, black.stones), (secs(60), 2));
+    }
+
+    #[test]
+    fn test_clock_stops_while_scoring() {
+        let t0 = Instant::now();
+        let (mut clock, mut game) = started(TimeControl::Absolute { main_time: 60 }, t0);
+
+        game.pass(Color::Black).unwrap();
+        clock.follow(&game, t0 + secs(5));
+        game.pass(Color::White).unwrap();
+        clock.follow(&game, t0 + secs(8));
+
+        assert_eq!(clock.deadline(), None);
+        let state = clock.state(t0 + secs(1000));
+        assert_eq!((state.black.main_ms, state.white.main_ms), (55_000, 57_000));
+    }
+
+    #[test]
+    fn test_unstarted_clock_does_not_run() {
+        let game = Game::new();
+        let mut clock = Clock::new(TimeControl::Absolute { main_time: 60 });
+        assert_eq!(clock.follow(&game, Instant::now()), None);
+        assert_eq!(clock.deadline(), None);
+    }
+}
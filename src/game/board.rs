@@ -1,85 +1,716 @@
-use super::types::{Color, Position};
+use super::history::{Move, MoveKind};
+use super::types::{BoardSize, Color, Position, MAX_BOARD_SIZE};
 use std::collections::HashSet;
+use std::ops::{BitAnd, BitOr, Not};
 
+/// Words needed for the largest board, including one guard column per row
+const WORDS: usize = (MAX_BOARD_SIZE * (MAX_BOARD_SIZE + 1)).div_ceil(64);
+
+/// Fixed-size bitset with one bit per intersection
+///
+/// Point (x, y) is bit `y * stride + x`, where `stride` is the board width plus a
+/// guard column that is never on the board, so horizontal shifts cannot wrap
+/// from one row into the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Bits([u64; WORDS]);
+
+impl Bits {
+    fn single(index: usize) -> Self {
+        let mut bits = Self::default();
+        bits.0[index / 64] |= 1 << (index % 64);
+        bits
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Move every bit `n` places towards higher indices (`n` < 64)
+    fn shl(&self, n: usize) -> Self {
+        let mut out = [0; WORDS];
+        for (i, word) in out.iter_mut().enumerate() {
+            *word = self.0[i] << n;
+            if i > 0 {
+                *word |= self.0[i - 1] >> (64 - n);
+            }
+        }
+        Self(out)
+    }
+
+    /// Move every bit `n` places towards lower indices (`n` < 64)
+    fn shr(&self, n: usize) -> Self {
+        let mut out = [0; WORDS];
+        for (i, word) in out.iter_mut().enumerate() {
+            *word = self.0[i] >> n;
+            if i + 1 < WORDS {
+                *word |= self.0[i + 1] << (64 - n);
+            }
+        }
+        Self(out)
+    }
+
+    /// Index of the lowest set bit
+    fn first(&self) -> Option<usize> {
+        self.0.iter()
+            .position(|&word| word != 0)
+            .map(|i| i * 64 + self.0[i].trailing_zeros() as usize)
+    }
+
+    /// Indices of the set bits, lowest first
+    fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(i, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                (rest != 0).then(|| {
+                    let bit = rest.trailing_zeros() as usize;
+                    rest &= rest - 1;
+                    i * 64 + bit
+                })
+            })
+        })
+    }
+}
+
+impl BitAnd for Bits {
+    type Output = Self;
+
+    fn bitand(mut self, rhs: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(rhs.0) {
+            *a &= b;
+        }
+        self
+    }
+}
+
+impl BitOr for Bits {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(rhs.0) {
+            *a |= b;
+        }
+        self
+    }
+}
+
+impl Not for Bits {
+    type Output = Self;
+
+    fn not(mut self) -> Self {
+        for word in self.0.iter_mut() {
+            *word = !*word;
+        }
+        self
+    }
+}
+
+/// A maximal group of connected stones of one color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Chain {
+    pub color: Color,
+    pub stones: Bits,
+    pub liberties: Bits,
+}
+
+/// Stones on the board as one bitset per color
+///
+/// Chains are kept up to date on every change: each stone points at the slot of
+/// the chain it belongs to, so group and liberty lookups never flood fill.
+#[derive(Clone)]
 pub(crate) struct Board {
-    pub(crate) size: usize,
-    pub(crate) grid: Vec<Vec<Option<Color>>>,
+    size: BoardSize,
+    stride: usize,   // Bits per row: the width plus the guard column
+    on_board: Bits,  // Every intersection of the board
+    black: Bits,
+    white: Bits,
+    chain_of: Vec<Option<u16>>, // Chain slot of the stone at each bit index
+    chains: Vec<Option<Chain>>,
+    free_slots: Vec<u16>,
+    hash: u64, // Zobrist hash, updated on every change
 }
 
 impl Board {
+    #[cfg(test)]
     pub fn new() -> Self {
-        Self::with_size(19)
+        Self::with_size(BoardSize::default())
     }
 
-    pub fn with_size(size: usize) -> Self {
+    pub fn with_size(size: BoardSize) -> Self {
+        let stride = size.width() + 1;
+        let mut on_board = Bits::default();
+        for y in 0..size.height() {
+            for x in 0..size.width() {
+                on_board = on_board | Bits::single(y * stride + x);
+            }
+        }
+
         Self {
             size,
-            grid: vec![vec![None; size]; size],
+            stride,
+            on_board,
+            black: Bits::default(),
+            white: Bits::default(),
+            chain_of: vec![None; stride * size.height()],
+            chains: Vec::new(),
+            free_slots: Vec::new(),
+            hash: 0,
         }
     }
 
+    fn index(&self, pos: Position) -> usize {
+        pos.y * self.stride + pos.x
+    }
+
+    fn position(&self, index: usize) -> Position {
+        Position::new(index % self.stride, index / self.stride)
+    }
+
     pub fn get(&self, pos: Position) -> Option<Color> {
-        if pos.is_valid_for_size(self.size) {
-            self.grid[pos.y][pos.x]
-        } else {
-            None
-        }
+        self.chain_at(pos).map(|chain| chain.color)
     }
 
     pub fn set(&mut self, pos: Position, color: Option<Color>) {
-        if pos.is_valid_for_size(self.size) {
-            self.grid[pos.y][pos.x] = color;
+        if !pos.is_valid(self.size) {
+            return;
+        }
+
+        let bit = self.point(pos);
+        if self.get(pos).is_some() {
+            self.remove(bit);
+        }
+        if let Some(color) = color {
+            self.add(bit, color);
+        }
+    }
+
+    /// Remove stones from the board, e.g. the groups taken by a capture
+    pub(crate) fn remove_stones(&mut self, stones: &[Position]) {
+        let bits = stones.iter()
+            .filter(|pos| pos.is_valid(self.size))
+            .fold(Bits::default(), |bits, &pos| bits | self.point(pos));
+        self.remove(bits & (self.black | self.white));
+    }
+
+    /// Take back a stone move: lift the stone and put back everything it removed
+    pub(crate) fn undo(&mut self, mv: &Move) {
+        let MoveKind::Stone(pos) = mv.kind else {
+            return;
+        };
+
+        for &own in mv.self_captures.iter().filter(|&&own| own != pos) {
+            self.set(own, Some(mv.color));
         }
+        self.set(pos, None);
+        for &captured in &mv.captures {
+            self.set(captured, Some(mv.color.opposite()));
+        }
+    }
+
+    /// Zobrist hash of the stones on the board (the empty board hashes to 0)
+    pub fn hash(&self) -> u64 {
+        self.hash
     }
 
     pub fn is_empty(&self, pos: Position) -> bool {
         self.get(pos).is_none()
     }
 
-    pub fn size(&self) -> usize {
+    pub fn size(&self) -> BoardSize {
         self.size
     }
 
+    /// Copy the grid out as rows of intersections (indexed [y][x])
+    pub fn to_grid(&self) -> Vec<Vec<Option<Color>>> {
+        (0..self.size.height())
+            .map(|y| (0..self.size.width()).map(|x| self.get(Position::new(x, y))).collect())
+            .collect()
+    }
+
+    /// The chain the stone at `pos` belongs to
+    pub(crate) fn chain_at(&self, pos: Position) -> Option<&Chain> {
+        if !pos.is_valid(self.size) {
+            return None;
+        }
+        self.chain_of[self.index(pos)].and_then(|slot| self.chains[slot as usize].as_ref())
+    }
+
+    /// Every chain on the board, in no particular order
+    pub(crate) fn chains(&self) -> impl Iterator<Item = &Chain> {
+        self.chains.iter().flatten()
+    }
+
+    /// The bit for a single intersection
+    pub(crate) fn point(&self, pos: Position) -> Bits {
+        Bits::single(self.index(pos))
+    }
+
+    /// Empty intersections
+    pub(crate) fn empty(&self) -> Bits {
+        self.on_board & !(self.black | self.white)
+    }
+
+    /// Intersections orthogonally adjacent to any of `bits`
+    pub(crate) fn neighbors(&self, bits: Bits) -> Bits {
+        let spread = bits.shl(1) | bits.shr(1) | bits.shl(self.stride) | bits.shr(self.stride);
+        spread & self.on_board
+    }
+
+    /// Positions of the set bits
+    pub(crate) fn positions(&self, bits: Bits) -> Vec<Position> {
+        bits.indices().map(|index| self.position(index)).collect()
+    }
+
     /// Find all stones in the same group as the stone at the given position
-    /// Uses flood fill to find connected stones of the same color
     pub(crate) fn find_group(&self, pos: Position) -> HashSet<Position> {
-        let mut group = HashSet::new();
-        let stone_color = match self.get(pos) {
-            Some(color) => color,
-            None => return group, // No stone at position
+        self.chain_at(pos)
+            .map(|chain| self.positions(chain.stones).into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Count liberties for a stone at the given position
+    pub(crate) fn count_liberties(&self, pos: Position) -> usize {
+        self.chain_at(pos).map_or(0, |chain| chain.liberties.count())
+    }
+
+    /// Slots of the chains owning any of the stones in `bits`
+    fn slots(&self, bits: Bits) -> Vec<u16> {
+        let mut slots = Vec::new();
+        for index in bits.indices() {
+            if let Some(slot) = self.chain_of[index] {
+                if !slots.contains(&slot) {
+                    slots.push(slot);
+                }
+            }
+        }
+        slots
+    }
+
+    fn chain_mut(&mut self, slot: u16) -> &mut Chain {
+        self.chains[slot as usize].as_mut().expect("stone points at a freed chain")
+    }
+
+    /// Store a chain in a free slot and point its stones at it
+    fn insert_chain(&mut self, chain: Chain) -> u16 {
+        let slot = match self.free_slots.pop() {
+            Some(slot) => slot,
+            None => {
+                self.chains.push(None);
+                (self.chains.len() - 1) as u16
+            }
         };
+        for index in chain.stones.indices() {
+            self.chain_of[index] = Some(slot);
+        }
+        self.chains[slot as usize] = Some(chain);
+        slot
+    }
 
-        let mut to_visit = vec![pos];
-        group.insert(pos);
+    fn take_chain(&mut self, slot: u16) -> Chain {
+        self.free_slots.push(slot);
+        self.chains[slot as usize].take().expect("chain slot freed twice")
+    }
 
-        while let Some(current) = to_visit.pop() {
-            for adjacent in current.adjacent_for_size(self.size) {
-                if group.contains(&adjacent) {
-                    continue; // Already visited
+    /// Put a stone on an empty intersection, merging it with friendly neighbors
+    fn add(&mut self, bit: Bits, color: Color) {
+        let index = bit.first().expect("a single intersection");
+        self.hash ^= zobrist_key(self.position(index), color);
+        match color {
+            Color::Black => self.black = self.black | bit,
+            Color::White => self.white = self.white | bit,
+        }
+
+        // Every neighboring chain loses this liberty; friendly ones join the new stone
+        let mut friendly = Vec::new();
+        for slot in self.slots(self.neighbors(bit)) {
+            let chain = self.chain_mut(slot);
+            chain.liberties = chain.liberties & !bit;
+            if chain.color == color {
+                friendly.push(slot);
+            }
+        }
+
+        let liberties = self.neighbors(bit) & self.empty();
+        let Some(largest) = friendly.iter().copied().max_by_key(|&slot| self.chains[slot as usize].unwrap().stones.count()) else {
+            self.insert_chain(Chain { color, stones: bit, liberties });
+            return;
+        };
+
+        // Keep the largest chain's slot so the fewest stones are relabeled
+        let mut merged = Chain { color, stones: bit, liberties };
+        for slot in friendly.into_iter().filter(|&slot| slot != largest) {
+            let chain = self.take_chain(slot);
+            merged.stones = merged.stones | chain.stones;
+            merged.liberties = merged.liberties | chain.liberties;
+        }
+        for index in merged.stones.indices() {
+            self.chain_of[index] = Some(largest);
+        }
+
+        let chain = self.chain_mut(largest);
+        chain.stones = chain.stones | merged.stones;
+        chain.liberties = chain.liberties | merged.liberties;
+    }
+
+    /// Take stones off the board, splitting chains that lose connecting stones
+    fn remove(&mut self, stones: Bits) {
+        let affected = self.slots(stones);
+
+        for index in stones.indices() {
+            let pos = self.position(index);
+            if let Some(color) = self.get(pos) {
+                self.hash ^= zobrist_key(pos, color);
+            }
+            self.chain_of[index] = None;
+        }
+        self.black = self.black & !stones;
+        self.white = self.white & !stones;
+
+        for slot in affected {
+            let chain = self.take_chain(slot);
+            let mut rest = chain.stones & !stones;
+
+            // Whatever is left of a partly removed chain may have fallen apart
+            while let Some(index) = rest.first() {
+                let mut part = Bits::single(index);
+                loop {
+                    let grown = (part | self.neighbors(part)) & rest;
+                    if grown == part {
+                        break;
+                    }
+                    part = grown;
                 }
-                if self.get(adjacent) == Some(stone_color) {
-                    group.insert(adjacent);
-                    to_visit.push(adjacent);
+                rest = rest & !part;
+
+                let liberties = self.neighbors(part) & self.empty();
+                self.insert_chain(Chain { color: chain.color, stones: part, liberties });
+            }
+        }
+
+        // Neighbors of the removed stones gain them as liberties
+        for slot in self.slots(self.neighbors(stones)) {
+            let empty = self.empty();
+            let stones = self.chain_mut(slot).stones;
+            let liberties = self.neighbors(stones) & empty;
+            self.chain_mut(slot).liberties = liberties;
+        }
+    }
+}
+
+/// Zobrist key for a stone of `color` at `pos`
+///
+/// Keys are derived with the SplitMix64 finalizer instead of a lookup table,
+/// so they are fixed across runs and independent of board size.
+fn zobrist_key(pos: Position, color: Color) -> u64 {
+    let color_index = match color {
+        Color::Black => 0,
+        Color::White => 1,
+    };
+    let mut z = ((pos.y as u64) << 33 | (pos.x as u64) << 1 | color_index)
+        .wrapping_add(1)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::rules;
+    use std::time::Instant;
+
+    #[test]
+    fn test_neighbors_do_not_wrap_rows() {
+        let board = Board::with_size(BoardSize::new(5, 3).unwrap());
+
+        let right_edge = board.neighbors(board.point(Position::new(4, 1)));
+        let mut expected = board.positions(right_edge);
+        expected.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(expected, vec![Position::new(4, 0), Position::new(3, 1), Position::new(4, 2)]);
+
+        let corner = board.neighbors(board.point(Position::new(0, 0)));
+        assert_eq!(corner.count(), 2);
+    }
+
+    #[test]
+    fn test_largest_board_fits() {
+        let size = BoardSize::square(MAX_BOARD_SIZE).unwrap();
+        let mut board = Board::with_size(size);
+        let corner = Position::new(MAX_BOARD_SIZE - 1, MAX_BOARD_SIZE - 1);
+
+        board.set(corner, Some(Color::White));
+        assert_eq!(board.get(corner), Some(Color::White));
+        assert_eq!(board.count_liberties(corner), 2);
+        assert_eq!(board.empty().count(), size.area() - 1);
+    }
+
+    #[test]
+    fn test_set_replaces_stone_and_hash() {
+        let mut board = Board::new();
+        let pos = Position::new(3, 3);
+
+        board.set(pos, Some(Color::Black));
+        board.set(pos, Some(Color::White));
+        assert_eq!(board.get(pos), Some(Color::White));
+        assert_eq!(board.chain_at(pos).unwrap().liberties.count(), 4);
+
+        board.set(pos, None);
+        assert_eq!(board.hash(), 0);
+    }
+
+    #[test]
+    fn test_chains_merge_and_split() {
+        let mut board = Board::new();
+        let left = Position::new(3, 3);
+        let middle = Position::new(4, 3);
+        let right = Position::new(5, 3);
+
+        board.set(left, Some(Color::Black));
+        board.set(right, Some(Color::Black));
+        assert_ne!(board.chain_at(left), board.chain_at(right));
+
+        board.set(middle, Some(Color::Black));
+        let chain = *board.chain_at(left).unwrap();
+        assert_eq!(board.chain_at(right), Some(&chain));
+        assert_eq!(chain.stones.count(), 3);
+        assert_eq!(chain.liberties.count(), 8);
+
+        board.set(middle, None);
+        assert_eq!(board.chain_at(left).unwrap().stones.count(), 1);
+        assert_eq!(board.count_liberties(left), 4);
+        assert_eq!(board.count_liberties(right), 4);
+    }
+
+    #[test]
+    fn test_capture_gives_liberties_back() {
+        let mut board = Board::new();
+        board.set(Position::new(0, 0), Some(Color::White));
+        board.set(Position::new(1, 0), Some(Color::Black));
+        board.set(Position::new(0, 1), Some(Color::Black));
+        assert_eq!(board.count_liberties(Position::new(0, 0)), 0);
+        assert_eq!(board.count_liberties(Position::new(1, 0)), 2);
+
+        board.remove_stones(&[Position::new(0, 0)]);
+        assert_eq!(board.count_liberties(Position::new(1, 0)), 3);
+        assert_eq!(board.count_liberties(Position::new(0, 1)), 3);
+    }
+
+    /// Compare every tracked chain against a fresh flood fill
+    fn assert_chains_consistent(board: &Board) {
+        let size = board.size();
+        for y in 0..size.height() {
+            for x in 0..size.width() {
+                let pos = Position::new(x, y);
+                let Some(color) = board.get(pos) else {
+                    assert!(board.chain_at(pos).is_none());
+                    continue;
+                };
+
+                let mut group = HashSet::from([pos]);
+                let mut liberties = HashSet::new();
+                let mut to_visit = vec![pos];
+                while let Some(current) = to_visit.pop() {
+                    for adjacent in current.adjacent(size) {
+                        match board.get(adjacent) {
+                            None => {
+                                liberties.insert(adjacent);
+                            }
+                            Some(c) if c == color && group.insert(adjacent) => to_visit.push(adjacent),
+                            Some(_) => {}
+                        }
+                    }
                 }
+
+                let chain = board.chain_at(pos).unwrap();
+                assert_eq!(chain.color, color);
+                assert_eq!(board.positions(chain.stones).into_iter().collect::<HashSet<_>>(), group, "stones at {:?}", pos);
+                assert_eq!(board.positions(chain.liberties).into_iter().collect::<HashSet<_>>(), liberties, "liberties at {:?}", pos);
             }
         }
+    }
 
-        group
+    #[test]
+    fn test_chains_stay_consistent() {
+        let size = BoardSize::new(9, 7).unwrap();
+        let mut board = Board::with_size(size);
+        let mut rng = Rng(7);
+
+        // Random stones and removals, including removals that cut chains apart
+        for step in 0..600 {
+            let pos = Position::new(rng.below(size.width()), rng.below(size.height()));
+            let color = match rng.below(3) {
+                0 => None,
+                1 => Some(Color::Black),
+                _ => Some(Color::White),
+            };
+            board.set(pos, color);
+            if step % 50 == 0 {
+                let captured = board.find_group(pos).into_iter().collect::<Vec<_>>();
+                board.remove_stones(&captured);
+            }
+            assert_chains_consistent(&board);
+        }
     }
 
-    /// Count liberties for a stone at the given position
-    pub(crate) fn count_liberties(&self, pos: Position) -> usize {
-        let group = self.find_group(pos);
-        let mut liberties = HashSet::new();
+    /// Reference implementation the bitboard replaced: a plain grid with flood fills,
+    /// a cloned board for the suicide check and a full-board capture scan
+    #[derive(Clone)]
+    struct GridBoard {
+        size: BoardSize,
+        grid: Vec<Vec<Option<Color>>>,
+    }
+
+    impl GridBoard {
+        fn new(size: BoardSize) -> Self {
+            Self { size, grid: vec![vec![None; size.width()]; size.height()] }
+        }
+
+        fn group(&self, pos: Position) -> HashSet<Position> {
+            let color = self.grid[pos.y][pos.x];
+            let mut group = HashSet::from([pos]);
+            let mut to_visit = vec![pos];
+            while let Some(current) = to_visit.pop() {
+                for adjacent in current.adjacent(self.size) {
+                    if self.grid[adjacent.y][adjacent.x] == color && group.insert(adjacent) {
+                        to_visit.push(adjacent);
+                    }
+                }
+            }
+            group
+        }
+
+        fn liberties(&self, pos: Position) -> usize {
+            let mut liberties = HashSet::new();
+            for stone in self.group(pos) {
+                for adjacent in stone.adjacent(self.size) {
+                    if self.grid[adjacent.y][adjacent.x].is_none() {
+                        liberties.insert(adjacent);
+                    }
+                }
+            }
+            liberties.len()
+        }
+
+        fn remove_captured(&mut self, color: Color) -> usize {
+            let mut checked = HashSet::new();
+            let mut captured = HashSet::new();
+            for y in 0..self.size.height() {
+                for x in 0..self.size.width() {
+                    let pos = Position::new(x, y);
+                    if self.grid[y][x] != Some(color) || checked.contains(&pos) {
+                        continue;
+                    }
+                    let group = self.group(pos);
+                    checked.extend(group.iter().copied());
+                    if self.liberties(pos) == 0 {
+                        captured.extend(group);
+                    }
+                }
+            }
+            for pos in &captured {
+                self.grid[pos.y][pos.x] = None;
+            }
+            captured.len()
+        }
+
+        fn play(&mut self, pos: Position, color: Color) -> bool {
+            let mut test = self.clone();
+            test.grid[pos.y][pos.x] = Some(color);
+            test.remove_captured(color.opposite());
+            if test.liberties(pos) == 0 {
+                return false;
+            }
 
-        for &stone_pos in &group {
-            for adjacent in stone_pos.adjacent_for_size(self.size) {
-                if self.is_empty(adjacent) {
-                    liberties.insert(adjacent);
+            self.grid[pos.y][pos.x] = Some(color);
+            self.remove_captured(color.opposite());
+            true
+        }
+    }
+
+    fn play_bitboard(board: &mut Board, pos: Position, color: Color) -> bool {
+        if rules::is_suicide(board, pos, color) {
+            return false;
+        }
+        board.set(pos, Some(color));
+        board.remove_stones(&rules::find_captures(board, pos, color.opposite()));
+        true
+    }
+
+    /// Deterministic xorshift generator so playouts are reproducible
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    /// Random moves (pass after a few illegal tries) until the board fills up or the move cap is hit
+    fn random_playout(size: BoardSize, seed: u64, mut play: impl FnMut(Position, Color) -> bool) {
+        let mut rng = Rng(seed);
+        let mut color = Color::Black;
+        for _ in 0..size.area() * 2 {
+            for _ in 0..8 {
+                let pos = Position::new(rng.below(size.width()), rng.below(size.height()));
+                if play(pos, color) {
+                    break;
                 }
             }
+            color = color.opposite();
+        }
+    }
+
+    fn bitboard_playout(size: BoardSize, seed: u64) -> Board {
+        let mut board = Board::with_size(size);
+        random_playout(size, seed, |pos, color| board.is_empty(pos) && play_bitboard(&mut board, pos, color));
+        board
+    }
+
+    fn grid_playout(size: BoardSize, seed: u64) -> GridBoard {
+        let mut board = GridBoard::new(size);
+        random_playout(size, seed, |pos, color| board.grid[pos.y][pos.x].is_none() && board.play(pos, color));
+        board
+    }
+
+    #[test]
+    fn test_random_playouts_match_grid_reference() {
+        for (size, seed) in [(BoardSize::square(9).unwrap(), 1), (BoardSize::new(13, 5).unwrap(), 2)] {
+            let board = bitboard_playout(size, seed);
+            assert_eq!(board.to_grid(), grid_playout(size, seed).grid, "{} playout diverged", size);
+            assert_chains_consistent(&board);
+        }
+    }
+
+    /// Timing comparison; run with `cargo test --release -- --ignored --nocapture bench`
+    #[test]
+    #[ignore]
+    fn bench_random_playouts() {
+        let size = BoardSize::default();
+        let playouts = 50;
+
+        let start = Instant::now();
+        for seed in 1..=playouts {
+            grid_playout(size, seed);
+        }
+        let grid = start.elapsed();
+
+        let start = Instant::now();
+        for seed in 1..=playouts {
+            bitboard_playout(size, seed);
         }
+        let bitboard = start.elapsed();
 
-        liberties.len()
+        println!(
+            "{} random 19x19 playouts: grid {:?}, bitboard {:?} ({:.1}x faster)",
+            playouts, grid, bitboard, grid.as_secs_f64() / bitboard.as_secs_f64()
+        );
+        assert!(bitboard < grid);
     }
 }
@@ -0,0 +1,75 @@
+use std::fmt;
+use super::types::Position;
+
+/// Why a move or pass was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    GameOver,
+    NotYourTurn,
+    HandicapPending, // Free handicap stones still have to be placed
+    OutOfBounds(Position),
+    Occupied(Position),
+    Suicide(Position),
+    Ko(Position),      // Immediate recapture
+    Superko(Position), // Repeats an earlier position
+}
+
+impl MoveError {
+    /// Stable machine-readable identifier sent to clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            MoveError::GameOver => "game_over",
+            MoveError::NotYourTurn => "not_your_turn",
+            MoveError::HandicapPending => "handicap_pending",
+            MoveError::OutOfBounds(_) => "out_of_bounds",
+            MoveError::Occupied(_) => "occupied",
+            MoveError::Suicide(_) => "suicide",
+            MoveError::Ko(_) => "ko",
+            MoveError::Superko(_) => "superko",
+        }
+    }
+
+    /// The intersection the rejected move was played on, if any
+    pub fn position(&self) -> Option<Position> {
+        match *self {
+            MoveError::OutOfBounds(pos)
+            | MoveError::Occupied(pos)
+            | MoveError::Suicide(pos)
+            | MoveError::Ko(pos)
+            | MoveError::Superko(pos) => Some(pos),
+            MoveError::GameOver | MoveError::NotYourTurn | MoveError::HandicapPending => None,
+        }
+    }
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MoveError::GameOver => "Game is over",
+            MoveError::NotYourTurn => "Not your turn",
+            MoveError::HandicapPending => "Place your handicap stones first",
+            MoveError::OutOfBounds(_) => "Invalid position",
+            MoveError::Occupied(_) => "Intersection occupied",
+            MoveError::Suicide(_) => "Suicide move not allowed",
+            MoveError::Ko(_) => "Ko rule violation",
+            MoveError::Superko(_) => "Superko rule violation",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_and_positions() {
+        let pos = Position::new(2, 3);
+        assert_eq!(MoveError::Superko(pos).code(), "superko");
+        assert_eq!(MoveError::Occupied(pos).position(), Some(pos));
+        assert_eq!(MoveError::NotYourTurn.position(), None);
+        assert_eq!(MoveError::Ko(pos).to_string(), "Ko rule violation");
+    }
+}
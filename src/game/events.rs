@@ -0,0 +1,18 @@
+use serde::Serialize;
+use super::types::{Color, GameResult, Group, Position};
+
+/// Stone count from which a group is large (the "legion" stage of the stone evolution themes)
+pub const LARGE_GROUP: usize = 11;
+
+/// Something that happened in the game, for themes to hang sounds and animations on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    StonePlaced { color: Color, position: Position },
+    GroupCaptured { color: Color, size: usize }, // Color of the captured stones
+    Atari { group: Group },                      // Opponent group left with one liberty
+    KoStarted { point: Position },
+    LargeGroupFormed { group: Group },
+    Pass { color: Color },
+    GameEnded { result: GameResult },
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use super::types::{BoardSize, Position};
+
+/// Most handicap stones a game can start with
+pub const MAX_HANDICAP: u8 = 9;
+
+/// How Black's handicap stones are put on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandicapPlacement {
+    #[default]
+    Fixed, // Standard star points
+    Free,  // Black chooses the points before White's first move
+}
+
+/// Standard star-point layout for `stones` handicap stones (GTP `fixed_handicap` order)
+pub fn fixed_points(size: BoardSize, stones: u8) -> Result<Vec<Position>, String> {
+    if !size.is_square() || !matches!(size.width(), 9 | 13 | 19) {
+        return Err("Fixed handicap is only available on 9x9, 13x13 and 19x19 boards".to_string());
+    }
+    if !(2..=MAX_HANDICAP).contains(&stones) {
+        return Err(format!("Handicap must be between 2 and {}", MAX_HANDICAP));
+    }
+
+    let size = size.width();
+
+    // Corner stars sit on the 4th line (3rd line on 9x9); side and center stars mid-board
+    let near = if size >= 13 { 3 } else { 2 };
+    let far = size - 1 - near;
+    let mid = size / 2;
+
+    let corners = [
+        Position::new(near, far), // D4
+        Position::new(far, near), // Q16
+        Position::new(near, near), // D16
+        Position::new(far, far),  // Q4
+    ];
+    let sides = [
+        Position::new(near, mid), // D10
+        Position::new(far, mid),  // Q10
+        Position::new(mid, far),  // K4
+        Position::new(mid, near), // K16
+    ];
+    let center = Position::new(mid, mid);
+
+    let stones = stones as usize;
+    let mut points: Vec<Position> = corners.iter().take(stones).copied().collect();
+
+    if stones >= 6 {
+        // Even counts fill the sides in pairs; odd counts add the center
+        points.extend(sides.iter().take((stones - 4) / 2 * 2));
+    }
+    if stones >= 5 && stones % 2 == 1 {
+        points.push(center);
+    }
+
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(size: usize) -> BoardSize {
+        BoardSize::square(size).unwrap()
+    }
+
+    #[test]
+    fn test_fixed_points_19x19() {
+        let two = fixed_points(square(19), 2).unwrap();
+        assert_eq!(two, vec![Position::new(3, 15), Position::new(15, 3)]);
+
+        let five = fixed_points(square(19), 5).unwrap();
+        assert_eq!(five.len(), 5);
+        assert!(five.contains(&Position::new(9, 9)));
+
+        let six = fixed_points(square(19), 6).unwrap();
+        assert!(!six.contains(&Position::new(9, 9)));
+        assert!(six.contains(&Position::new(3, 9)));
+        assert!(six.contains(&Position::new(15, 9)));
+
+        let nine = fixed_points(square(19), 9).unwrap();
+        assert_eq!(nine.len(), 9);
+        assert!(nine.contains(&Position::new(9, 3)));
+        assert!(nine.contains(&Position::new(9, 15)));
+    }
+
+    #[test]
+    fn test_fixed_points_small_boards() {
+        assert_eq!(
+            fixed_points(square(9), 4).unwrap(),
+            vec![Position::new(2, 6), Position::new(6, 2), Position::new(2, 2), Position::new(6, 6)],
+        );
+        assert!(fixed_points(square(13), 7).unwrap().contains(&Position::new(6, 6)));
+        assert!(fixed_points(square(13), 8).unwrap().contains(&Position::new(6, 9)));
+    }
+
+    #[test]
+    fn test_fixed_points_unique() {
+        for size in [9, 13, 19] {
+            for stones in 2..=MAX_HANDICAP {
+                let mut points = fixed_points(square(size), stones).unwrap();
+                assert_eq!(points.len(), stones as usize);
+                points.sort_by_key(|p| (p.x, p.y));
+                points.dedup();
+                assert_eq!(points.len(), stones as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fixed_points_invalid() {
+        assert!(fixed_points(square(19), 1).is_err());
+        assert!(fixed_points(square(19), 10).is_err());
+        assert!(fixed_points(square(15), 4).is_err());
+        assert!(fixed_points(BoardSize::new(19, 13).unwrap(), 4).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use serde::Serialize;
+use super::board::Board;
+use super::types::{BoardSize, Color, Position};
+
+/// What a player did on their turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum MoveKind {
+    Stone(Position),
+    Pass,
+    Resign,
+}
+
+/// A single entry in the game record
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Move {
+    pub color: Color,
+    #[serde(flatten)]
+    pub kind: MoveKind,
+    /// Opponent stones removed by this move
+    pub captures: Vec<Position>,
+    /// Own stones removed by a suicide move (only where the ruleset allows it)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub self_captures: Vec<Position>,
+}
+
+/// What a stone placement did to the board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MoveOutcome {
+    pub position: Position,
+    /// Opponent stones removed by the move
+    pub captures: Vec<Position>,
+    /// Own stones removed by a suicide move
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub self_captures: Vec<Position>,
+    /// Point the opponent may not retake on their next move
+    pub ko: Option<Position>,
+    /// Number of moves played so far, this one included (0 for handicap stones)
+    pub move_number: usize,
+}
+
+/// The position a stone placement would lead to, without it being played
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovePreview {
+    pub board: Vec<Vec<Option<Color>>>,
+    pub outcome: MoveOutcome,
+}
+
+/// Read-only cursor over a game's move list
+///
+/// Keeps its own board, so browsing never touches the live game.
+pub struct Replay<'a> {
+    moves: &'a [Move],
+    board: Board,
+    move_number: usize,
+}
+
+impl<'a> Replay<'a> {
+    pub(super) fn new(moves: &'a [Move], setup: &[(Position, Color)], size: BoardSize) -> Self {
+        let mut board = Board::with_size(size);
+        for &(pos, color) in setup {
+            board.set(pos, Some(color));
+        }
+
+        Self {
+            moves,
+            board,
+            move_number: 0,
+        }
+    }
+
+    /// Number of moves applied to the replay board
+    pub fn move_number(&self) -> usize {
+        self.move_number
+    }
+
+    /// The move that produced the current position, if any
+    pub fn last_move(&self) -> Option<&'a Move> {
+        self.move_number.checked_sub(1).map(|i| &self.moves[i])
+    }
+
+    /// Apply the next move. Returns false at the end of the record.
+    pub fn forward(&mut self) -> bool {
+        let Some(mv) = self.moves.get(self.move_number) else {
+            return false;
+        };
+
+        if let MoveKind::Stone(pos) = mv.kind {
+            self.board.set(pos, Some(mv.color));
+            self.board.remove_stones(&mv.captures);
+            self.board.remove_stones(&mv.self_captures);
+        }
+
+        self.move_number += 1;
+        true
+    }
+
+    /// Undo the last applied move. Returns false at the start of the record.
+    pub fn back(&mut self) -> bool {
+        let Some(mv) = self.last_move() else {
+            return false;
+        };
+
+        self.board.undo(mv);
+        self.move_number -= 1;
+        true
+    }
+
+    /// Jump to the position after `move_number` moves (clamped to the record)
+    pub fn goto(&mut self, move_number: usize) {
+        let target = move_number.min(self.moves.len());
+        while self.move_number < target && self.forward() {}
+        while self.move_number > target && self.back() {}
+    }
+
+    /// Get the replay board as a 2D vector for serialization
+    pub fn board(&self) -> Vec<Vec<Option<Color>>> {
+        self.board.to_grid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{Color, Game, MoveKind, Position};
+
+    fn capture_game() -> Game {
+        let mut game = Game::new();
+        game.place_stone(Position::new(5, 4), Color::Black).unwrap();
+        game.place_stone(Position::new(5, 5), Color::White).unwrap();
+        game.place_stone(Position::new(4, 5), Color::Black).unwrap();
+        game.pass(Color::White).unwrap();
+        game.place_stone(Position::new(6, 5), Color::Black).unwrap();
+        game.place_stone(Position::new(11, 11), Color::White).unwrap();
+        game.place_stone(Position::new(5, 6), Color::Black).unwrap(); // Captures (5,5)
+        game
+    }
+
+    #[test]
+    fn test_moves_are_recorded() {
+        let game = capture_game();
+        let moves = game.moves();
+
+        assert_eq!(moves.len(), 7);
+        assert_eq!(moves[0].kind, MoveKind::Stone(Position::new(5, 4)));
+        assert_eq!(moves[3].kind, MoveKind::Pass);
+        assert_eq!(moves[3].color, Color::White);
+        assert_eq!(moves[6].captures, vec![Position::new(5, 5)]);
+    }
+
+    #[test]
+    fn test_replay_rebuilds_live_board() {
+        let game = capture_game();
+        let mut replay = game.replay();
+
+        replay.goto(game.moves().len());
+        assert_eq!(replay.board(), game.get_board());
+    }
+
+    #[test]
+    fn test_replay_step_back_restores_captures() {
+        let game = capture_game();
+        let mut replay = game.replay();
+        replay.goto(7);
+
+        assert_eq!(replay.board()[5][5], None);
+        assert!(replay.back());
+        assert_eq!(replay.board()[5][5], Some(Color::White));
+        assert_eq!(replay.board()[6][5], None);
+        assert!(replay.forward());
+        assert_eq!(replay.board()[5][5], None);
+    }
+
+    #[test]
+    fn test_replay_bounds() {
+        let game = capture_game();
+        let mut replay = game.replay();
+
+        assert!(!replay.back());
+        assert!(replay.last_move().is_none());
+
+        replay.goto(100);
+        assert_eq!(replay.move_number(), 7);
+        assert!(!replay.forward());
+
+        replay.goto(2);
+        assert_eq!(replay.move_number(), 2);
+        assert_eq!(replay.last_move().unwrap().color, Color::White);
+        assert_eq!(replay.board()[5][5], Some(Color::White));
+        assert_eq!(replay.board()[5][4], None);
+    }
+
+    #[test]
+    fn test_replay_leaves_live_game_untouched() {
+        let game = capture_game();
+        let live = game.get_board();
+
+        let mut replay = game.replay();
+        replay.goto(3);
+
+        assert_eq!(game.get_board(), live);
+        assert_eq!(game.moves().len(), 7);
+    }
+}
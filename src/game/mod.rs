@@ -1,121 +1,438 @@
 mod board;
+mod error;
+mod events;
+mod handicap;
+mod history;
 mod rules;
+mod ruleset;
 mod types;
 
-pub use types::{Color, Position};
+use std::collections::HashSet;
+
+pub use error::MoveError;
+pub use events::GameEvent;
+pub use handicap::HandicapPlacement;
+pub use history::{Move, MoveKind, MoveOutcome, MovePreview, Replay};
+pub use ruleset::{HandicapCompensation, KoRule, Ruleset, RulesetChoice, RulesetName};
+pub use types::{BoardSize, Color, GamePhase, GameResult, Group, Position, Score, ScoringMethod};
 
 /// Main game state
+#[derive(Clone)]
 pub struct Game {
     board: board::Board,
     turn: Color,
+    phase: GamePhase,
+    passes: u8,            // Consecutive passes
     prisoners: (u32, u32), // (black_captured, white_captured)
-    history: Vec<u64>,     // Board hashes for ko detection
+    history: Vec<(u64, Color)>, // Starting position, then the board after each stone or pass, with the player to move next
+    moves: Vec<Move>,      // Ordered game record
+    setup: Vec<(Position, Color)>, // Stones placed before the first move
+    handicap: u8,
+    handicap_to_place: u8, // Free placement stones Black still has to put down
+    ruleset: Ruleset,
+    player_names: (Option<String>, Option<String>), // (black, white)
+    dead_stones: HashSet<Position>, // Marked during the scoring phase
+    score_accepted: (bool, bool),   // (black, white)
+    final_score: Option<Score>,     // Locked once both players accept
+    result: Option<GameResult>,     // Set when the game is finished
+    events: Vec<GameEvent>,         // Happened since clients were last told
 }
 
 impl Game {
     pub fn new() -> Self {
-        Self::with_size(19)
+        Self::with_size(BoardSize::default(), Ruleset::default())
     }
 
-    pub fn with_size(size: usize) -> Self {
+    pub fn with_size(size: BoardSize, ruleset: Ruleset) -> Self {
         Self {
             board: board::Board::with_size(size),
             turn: Color::Black,
+            phase: GamePhase::Playing,
+            passes: 0,
             prisoners: (0, 0),
-            history: Vec::new(),
+            history: vec![(0, Color::Black)],
+            moves: Vec::new(),
+            setup: Vec::new(),
+            handicap: 0,
+            handicap_to_place: 0,
+            ruleset,
+            player_names: (None, None),
+            dead_stones: HashSet::new(),
+            score_accepted: (false, false),
+            final_score: None,
+            result: None,
+            events: Vec::new(),
         }
     }
 
-    pub fn reset_with_size(&mut self, size: usize) {
-        *self = Self::with_size(size);
+    /// Give Black `stones` handicap stones (0 for an even game). White moves first afterwards.
+    ///
+    /// Fixed placement puts them on the star points now; free placement lets Black
+    /// choose them with `place_stone` before White's first move.
+    pub fn setup_handicap(&mut self, stones: u8, placement: HandicapPlacement) -> Result<(), String> {
+        if stones == 0 {
+            return Ok(());
+        }
+        if !self.moves.is_empty() || !self.setup.is_empty() {
+            return Err("Handicap can only be set on an empty board".to_string());
+        }
+
+        match placement {
+            HandicapPlacement::Fixed => {
+                for pos in handicap::fixed_points(self.board.size(), stones)? {
+                    self.setup_stone(pos, Some(Color::Black))?;
+                }
+                self.set_first_player(Color::White)?;
+            }
+            HandicapPlacement::Free => {
+                if !(2..=handicap::MAX_HANDICAP).contains(&stones) {
+                    return Err(format!("Handicap must be between 2 and {}", handicap::MAX_HANDICAP));
+                }
+                if stones as usize >= self.board.size().area() {
+                    return Err("Too many handicap stones for this board".to_string());
+                }
+                self.handicap_to_place = stones;
+                self.set_first_player(Color::Black)?;
+            }
+        }
+
+        self.handicap = stones;
+        Ok(())
     }
 
     /// Attempt to place a stone at the given position
-    pub fn place_stone(&mut self, pos: Position, color: Color) -> Result<(), String> {
-        // 1. Check if it's the right player's turn
+    pub fn place_stone(&mut self, pos: Position, color: Color) -> Result<MoveOutcome, MoveError> {
+        // 1. Check the game is still being played and it's the right player's turn
+        if self.phase != GamePhase::Playing {
+            return Err(MoveError::GameOver);
+        }
         if color != self.turn {
-            return Err("Not your turn".to_string());
+            return Err(MoveError::NotYourTurn);
         }
 
         // 2. Check if position is valid
-        if !pos.is_valid() {
-            return Err("Invalid position".to_string());
+        if !pos.is_valid(self.board.size()) {
+            return Err(MoveError::OutOfBounds(pos));
         }
 
         // 3. Check if intersection is empty
         if !self.board.is_empty(pos) {
-            return Err("Intersection occupied".to_string());
+            return Err(MoveError::Occupied(pos));
         }
 
-        // 4. Check suicide rule
-        if rules::is_suicide(&self.board, pos, color) {
-            return Err("Suicide move not allowed".to_string());
+        // Free handicap stones are setup, not moves; White starts once they are down
+        if self.handicap_to_place > 0 {
+            self.put_setup_stone(pos, Some(color));
+            self.handicap_to_place -= 1;
+            if self.handicap_to_place == 0 {
+                self.start_with(Color::White);
+            }
+            self.events.push(GameEvent::StonePlaced { color, position: pos });
+            return Ok(MoveOutcome {
+                position: pos,
+                captures: Vec::new(),
+                self_captures: Vec::new(),
+                ko: None,
+                move_number: self.moves.len(),
+            });
         }
 
-        // 5. Save board state for potential rollback
-        let saved_board = self.board.grid.clone();
+        // Opponent groups whose last liberty this is; measured now, before they are gone
+        let opponent_color = color.opposite();
+        let captured_sizes: Vec<usize> = self.adjacent_chains(pos, opponent_color)
+            .filter(|chain| chain.liberties == self.board.point(pos))
+            .map(|chain| chain.stones.count())
+            .collect();
+        let largest_before = self.adjacent_chains(pos, color)
+            .map(|chain| chain.stones.count())
+            .max()
+            .unwrap_or(0);
 
-        // 6. Place the stone
-        self.board.set(pos, Some(color));
+        // 4-7. Place the stone, remove captures, and check the suicide and ko rules
+        let mv = rules::try_play(&mut self.board, &self.ruleset, &self.history, pos, color)?;
+        let board_hash = self.board.hash();
 
-        // 7. Remove captured opponent groups
-        let opponent_color = color.opposite();
-        let captures = rules::find_captures(&self.board, opponent_color);
-        let num_captures = captures.len() as u32;
+        // 8. Update prisoner count
+        let num_captures = mv.captures.len() as u32;
+        let num_self_captures = mv.self_captures.len() as u32;
+        match color {
+            Color::Black => {
+                self.prisoners.1 += num_captures; // Black captured white stones
+                self.prisoners.0 += num_self_captures;
+            }
+            Color::White => {
+                self.prisoners.0 += num_captures; // White captured black stones
+                self.prisoners.1 += num_self_captures;
+            }
+        }
+
+        // A lone stone that took a lone stone and sits in its only liberty can be retaken at once
+        let ko = match (&mv.captures[..], self.board.chain_at(pos)) {
+            ([captured], Some(chain)) if chain.stones.count() == 1 && chain.liberties.count() == 1 => Some(*captured),
+            _ => None,
+        };
 
-        for capture_pos in &captures {
-            self.board.set(*capture_pos, None);
+        self.events.push(GameEvent::StonePlaced { color, position: pos });
+        for size in captured_sizes {
+            self.events.push(GameEvent::GroupCaptured { color: opponent_color, size });
+        }
+        let ataris: Vec<Group> = self.adjacent_chains(pos, opponent_color)
+            .filter(|chain| chain.liberties.count() == 1)
+            .map(|chain| self.group(chain))
+            .collect();
+        self.events.extend(ataris.into_iter().map(|group| GameEvent::Atari { group }));
+        if let Some(point) = ko {
+            self.events.push(GameEvent::KoStarted { point });
+        }
+        if let Some(chain) = self.board.chain_at(pos) {
+            if chain.stones.count() >= events::LARGE_GROUP && largest_before < events::LARGE_GROUP {
+                let group = self.group(chain);
+                self.events.push(GameEvent::LargeGroupFormed { group });
+            }
         }
 
-        // 8. Check for ko violation
-        let board_hash = rules::hash_board(&self.board);
-        if rules::is_ko_violation(board_hash, &self.history) {
-            // Rollback the board
-            self.board.grid = saved_board;
-            return Err("Ko rule violation".to_string());
+        // 9. Update history
+        self.history.push((board_hash, opponent_color));
+        let outcome = MoveOutcome {
+            position: pos,
+            captures: mv.captures.clone(),
+            self_captures: mv.self_captures.clone(),
+            ko,
+            move_number: self.moves.len() + 1,
+        };
+        self.moves.push(mv);
+
+        // 10. Switch turn
+        self.turn = self.turn.opposite();
+        self.passes = 0;
+
+        Ok(outcome)
+    }
+
+    /// What playing `color` at `pos` would do, leaving the game untouched
+    ///
+    /// Either side may be previewed regardless of whose turn it is.
+    pub fn preview_move(&self, pos: Position, color: Color) -> Result<MovePreview, MoveError> {
+        let mut preview = self.clone();
+        preview.turn = color;
+        let outcome = preview.place_stone(pos, color)?;
+        Ok(MovePreview { board: preview.get_board(), outcome })
+    }
+
+    /// Every point the player to move may play on
+    ///
+    /// Suicide, ko and superko points are left out. Empty while the game is not being played.
+    pub fn legal_moves(&self) -> Vec<Position> {
+        if self.phase != GamePhase::Playing {
+            return Vec::new();
         }
 
-        // 9. Update prisoner count
-        match color {
-            Color::Black => self.prisoners.1 += num_captures, // Black captured white stones
-            Color::White => self.prisoners.0 += num_captures, // White captured black stones
+        let size = self.board.size();
+        let empty = (0..size.height())
+            .flat_map(|y| (0..size.width()).map(move |x| Position::new(x, y)))
+            .filter(|&pos| self.board.is_empty(pos));
+
+        // Free handicap stones may go on any empty point
+        if self.handicap_to_place > 0 {
+            return empty.collect();
         }
 
-        // 10. Update history
-        self.history.push(board_hash);
+        // Try each point on a scratch board, taking legal moves straight back
+        let mut board = self.board.clone();
+        empty
+            .filter(|&pos| match rules::try_play(&mut board, &self.ruleset, &self.history, pos, self.turn) {
+                Ok(mv) => {
+                    board.undo(&mv);
+                    true
+                }
+                Err(_) => false,
+            })
+            .collect()
+    }
 
-        // 11. Switch turn
+    /// Pass turn. Two consecutive passes move the game into scoring.
+    pub fn pass(&mut self, color: Color) -> Result<(), MoveError> {
+        if self.phase != GamePhase::Playing {
+            return Err(MoveError::GameOver);
+        }
+        if color != self.turn {
+            return Err(MoveError::NotYourTurn);
+        }
+        if self.handicap_to_place > 0 {
+            return Err(MoveError::HandicapPending);
+        }
+
+        self.moves.push(Move {
+            color,
+            kind: MoveKind::Pass,
+            captures: Vec::new(),
+            self_captures: Vec::new(),
+        });
         self.turn = self.turn.opposite();
+        self.history.push((self.board.hash(), self.turn)); // Same board, other player to move
+        self.passes += 1;
+        self.events.push(GameEvent::Pass { color });
+
+        if self.passes >= 2 {
+            self.phase = GamePhase::Scoring;
+        }
 
         Ok(())
     }
 
-    /// Pass turn
-    pub fn pass(&mut self) {
-        self.turn = self.turn.opposite();
+    /// Mark or unmark the whole group at `pos` as dead (scoring phase only)
+    pub fn toggle_dead(&mut self, pos: Position) -> Result<(), String> {
+        if self.phase != GamePhase::Scoring {
+            return Err("Not in scoring phase".to_string());
+        }
+        if self.board.is_empty(pos) {
+            return Err("No stone at that point".to_string());
+        }
+
+        let group = self.board.find_group(pos);
+        if group.iter().all(|p| self.dead_stones.contains(p)) {
+            for p in &group {
+                self.dead_stones.remove(p);
+            }
+        } else {
+            self.dead_stones.extend(group);
+        }
+
+        // Any change to the marking needs fresh agreement
+        self.score_accepted = (false, false);
+
+        Ok(())
+    }
+
+    /// Accept the current dead-stone marking. Once both accept, the score is locked.
+    pub fn accept_score(&mut self, color: Color) -> Result<(), String> {
+        if self.phase != GamePhase::Scoring {
+            return Err("Not in scoring phase".to_string());
+        }
+
+        match color {
+            Color::Black => self.score_accepted.0 = true,
+            Color::White => self.score_accepted.1 = true,
+        }
+
+        if self.score_accepted == (true, true) {
+            let score = self.score();
+            self.final_score = Some(score);
+            self.end(GameResult::from_margin(score.margin()));
+        }
+
+        Ok(())
+    }
+
+    /// Give up the game; allowed at any time until it is finished, on either player's turn
+    pub fn resign(&mut self, color: Color) -> Result<(), MoveError> {
+        if self.phase == GamePhase::Finished {
+            return Err(MoveError::GameOver);
+        }
+
+        self.moves.push(Move {
+            color,
+            kind: MoveKind::Resign,
+            captures: Vec::new(),
+            self_captures: Vec::new(),
+        });
+        self.end(GameResult::Resignation { winner: color.opposite() });
+
+        Ok(())
+    }
+
+    /// Finish the game with the given result
+    pub fn end(&mut self, result: GameResult) {
+        self.result = Some(result);
+        self.phase = GamePhase::Finished;
+        self.events.push(GameEvent::GameEnded { result });
+    }
+
+    /// Events since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Leave the scoring phase and continue playing; the opponent of `color` moves first
+    pub fn resume(&mut self, color: Color) -> Result<(), String> {
+        if self.phase != GamePhase::Scoring {
+            return Err("Not in scoring phase".to_string());
+        }
+
+        self.phase = GamePhase::Playing;
+        self.passes = 0;
+        self.turn = color.opposite();
+        self.dead_stones.clear();
+        self.score_accepted = (false, false);
+
+        Ok(())
+    }
+
+    /// Count the score with the current dead-stone marking
+    pub fn score(&self) -> Score {
+        let komi = self.ruleset.komi + self.ruleset.handicap_bonus(self.handicap);
+        rules::score(&self.board, &self.dead_stones, self.prisoners, komi, self.ruleset.scoring)
+    }
+
+    /// Territory points (black, white) under the current dead-stone marking
+    pub fn territory(&self) -> (Vec<Position>, Vec<Position>) {
+        rules::territory(&self.board, &self.dead_stones)
+    }
+
+    /// Restore a score agreed in a loaded record: mark `dead_stones` and lock the count
+    pub fn restore_score(&mut self, dead_stones: HashSet<Position>) {
+        self.dead_stones = dead_stones;
+        self.score_accepted = (true, true);
+        self.final_score = Some(self.score());
     }
 
     /// Reset game to initial state
+    #[cfg(test)]
     pub fn reset(&mut self) {
         *self = Self::new();
     }
 
     /// Get the current board state as a 2D vector for serialization
     pub fn get_board(&self) -> Vec<Vec<Option<Color>>> {
-        let size = self.board.size();
-        let mut result = Vec::with_capacity(size);
-        for y in 0..size {
-            let mut row = Vec::with_capacity(size);
-            for x in 0..size {
-                row.push(self.board.get(Position::new(x, y)));
-            }
-            result.push(row);
+        self.board.to_grid()
+    }
+
+    /// Groups on the board with their liberties, ordered by first stone
+    pub fn groups(&self) -> Vec<Group> {
+        let mut groups: Vec<Group> = self.board.chains().map(|chain| self.group(chain)).collect();
+        groups.sort_by_key(|group| group.id);
+        groups
+    }
+
+    fn group(&self, chain: &board::Chain) -> Group {
+        let stones = self.board.positions(chain.stones);
+        let liberties = chain.liberties.count();
+        Group {
+            id: stones[0].y * self.board.size().width() + stones[0].x,
+            color: chain.color,
+            stones,
+            liberties,
+            atari: liberties == 1,
         }
-        result
+    }
+
+    /// Distinct chains of `color` touching `pos`
+    fn adjacent_chains(&self, pos: Position, color: Color) -> impl Iterator<Item = &board::Chain> {
+        let mut seen = Vec::new();
+        pos.adjacent(self.board.size())
+            .into_iter()
+            .filter_map(|adjacent| self.board.chain_at(adjacent))
+            .filter(move |chain| {
+                if chain.color != color || seen.contains(&chain.stones) {
+                    return false;
+                }
+                seen.push(chain.stones);
+                true
+            })
     }
 
     /// Get the board size
-    pub fn get_board_size(&self) -> usize {
+    pub fn get_board_size(&self) -> BoardSize {
         self.board.size()
     }
 
@@ -124,10 +441,139 @@ impl Game {
         self.turn
     }
 
+    /// Get the current game phase
+    pub fn get_phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    /// Get the number of consecutive passes
+    pub fn get_passes(&self) -> u8 {
+        self.passes
+    }
+
     /// Get prisoner counts (black_captured, white_captured)
     pub fn get_prisoners(&self) -> (u32, u32) {
         self.prisoners
     }
+
+    /// Get komi (points added to White's score)
+    pub fn get_komi(&self) -> f32 {
+        self.ruleset.komi
+    }
+
+    /// Set komi
+    pub fn set_komi(&mut self, komi: f32) {
+        self.ruleset.komi = komi;
+    }
+
+    /// Get the rules this game is played under
+    pub fn get_ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    /// Get the number of handicap stones given to Black
+    pub fn get_handicap(&self) -> u8 {
+        self.handicap
+    }
+
+    /// Get the number of free handicap stones Black still has to place
+    pub fn get_handicap_to_place(&self) -> u8 {
+        self.handicap_to_place
+    }
+
+    /// Record the handicap (the stones themselves are placed as setup)
+    pub fn set_handicap(&mut self, handicap: u8) {
+        self.handicap = handicap;
+    }
+
+    /// Get stones placed on the board before the first move
+    pub fn get_setup(&self) -> &[(Position, Color)] {
+        &self.setup
+    }
+
+    /// Place or remove a stone before the first move (SGF `AB`/`AW`/`AE`)
+    pub fn setup_stone(&mut self, pos: Position, color: Option<Color>) -> Result<(), String> {
+        if !self.moves.is_empty() {
+            return Err("Setup is only allowed before the first move".to_string());
+        }
+        if !pos.is_valid(self.board.size()) {
+            return Err("Invalid position".to_string());
+        }
+
+        self.put_setup_stone(pos, color);
+        Ok(())
+    }
+
+    /// Choose who moves first (SGF `PL`)
+    pub fn set_first_player(&mut self, color: Color) -> Result<(), String> {
+        if !self.moves.is_empty() {
+            return Err("First player can only be set before the first move".to_string());
+        }
+        self.start_with(color);
+        Ok(())
+    }
+
+    /// Change a setup stone; the result becomes the starting position
+    fn put_setup_stone(&mut self, pos: Position, color: Option<Color>) {
+        self.setup.retain(|&(p, _)| p != pos);
+        if let Some(color) = color {
+            self.setup.push((pos, color));
+        }
+        self.board.set(pos, color);
+        self.history = vec![(self.board.hash(), self.turn)];
+    }
+
+    /// Set the player to move in the starting position
+    fn start_with(&mut self, color: Color) {
+        self.turn = color;
+        self.history = vec![(self.board.hash(), self.turn)];
+    }
+
+    /// Get the display name of a player, if one was given
+    pub fn get_player_name(&self, color: Color) -> Option<&str> {
+        match color {
+            Color::Black => self.player_names.0.as_deref(),
+            Color::White => self.player_names.1.as_deref(),
+        }
+    }
+
+    /// Set the display name of a player
+    pub fn set_player_name(&mut self, color: Color, name: String) {
+        match color {
+            Color::Black => self.player_names.0 = Some(name),
+            Color::White => self.player_names.1 = Some(name),
+        }
+    }
+
+    /// Get stones marked dead during scoring
+    pub fn get_dead_stones(&self) -> &HashSet<Position> {
+        &self.dead_stones
+    }
+
+    /// Get which players accepted the dead-stone marking (black, white)
+    pub fn get_score_accepted(&self) -> (bool, bool) {
+        self.score_accepted
+    }
+
+    /// Get the agreed score, once the game is finished
+    pub fn get_final_score(&self) -> Option<Score> {
+        self.final_score
+    }
+
+    /// How the game ended, once it has
+    pub fn get_result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// Get the ordered move list
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Browse the move list from the setup position without touching live state
+    pub fn replay(&self) -> Replay<'_> {
+        Replay::new(&self.moves, &self.setup, self.board.size())
+    }
 }
 
 #[cfg(test)]
@@ -158,7 +604,7 @@ mod tests {
         // Black tries to play again - should fail
         let result = game.place_stone(Position::new(4, 4), Color::Black);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Not your turn");
+        assert_eq!(result.unwrap_err(), MoveError::NotYourTurn);
 
         // White plays - should succeed
         assert!(game.place_stone(Position::new(4, 4), Color::White).is_ok());
@@ -173,7 +619,58 @@ mod tests {
         // Try to place white stone on same spot
         let result = game.place_stone(Position::new(5, 5), Color::White);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Intersection occupied");
+        assert_eq!(result.unwrap_err(), MoveError::Occupied(Position::new(5, 5)));
+    }
+
+    #[test]
+    fn test_off_board_move_rejected() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+
+        assert_eq!(
+            game.place_stone(Position::new(15, 15), Color::Black).unwrap_err(),
+            MoveError::OutOfBounds(Position::new(15, 15)),
+        );
+        assert_eq!(game.get_turn(), Color::Black);
+        assert!(game.moves().is_empty());
+    }
+
+    #[test]
+    fn test_groups_report_liberties_and_atari() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+
+        game.place_stone(Position::new(0, 0), Color::Black).unwrap();
+        game.place_stone(Position::new(1, 0), Color::White).unwrap();
+        game.place_stone(Position::new(1, 1), Color::Black).unwrap();
+        game.place_stone(Position::new(4, 4), Color::White).unwrap();
+        game.place_stone(Position::new(0, 1), Color::Black).unwrap();
+
+        let groups = game.groups();
+        assert_eq!(groups.len(), 3);
+
+        let corner = &groups[0];
+        assert_eq!((corner.id, corner.color), (0, Color::Black));
+        assert_eq!(corner.stones, vec![Position::new(0, 0), Position::new(0, 1), Position::new(1, 1)]);
+        assert_eq!((corner.liberties, corner.atari), (3, false));
+
+        let edge = &groups[1];
+        assert_eq!((edge.id, edge.color, edge.liberties, edge.atari), (1, Color::White, 1, true));
+
+        assert_eq!(groups[2].id, 4 * 9 + 4);
+        assert_eq!(groups[2].liberties, 4);
+    }
+
+    #[test]
+    fn test_rectangular_board_capture() {
+        let mut game = Game::with_size(BoardSize::new(7, 3).unwrap(), Ruleset::default());
+
+        // White stone in the far corner, captured against both edges
+        game.place_stone(Position::new(5, 2), Color::Black).unwrap();
+        game.place_stone(Position::new(6, 2), Color::White).unwrap();
+        game.place_stone(Position::new(6, 1), Color::Black).unwrap();
+
+        assert_eq!(game.get_board()[2][6], None);
+        assert_eq!(game.get_prisoners(), (0, 1));
+        assert!(game.place_stone(Position::new(3, 6), Color::White).is_err());
     }
 
     #[test]
@@ -216,7 +713,10 @@ mod tests {
         game.place_stone(Position::new(3, 1), Color::White).unwrap();
 
         // Black captures White at (1,1) by playing at (2,1)
-        game.place_stone(Position::new(2, 1), Color::Black).unwrap();
+        let outcome = game.place_stone(Position::new(2, 1), Color::Black).unwrap();
+        assert_eq!(outcome.captures, vec![Position::new(1, 1)]);
+        assert_eq!(outcome.ko, Some(Position::new(1, 1)));
+        assert_eq!(outcome.move_number, 9);
 
         // Now (1,1) is empty, and White at (1,1) was captured
         // If White plays at (1,1), it recaptures Black at (2,1)
@@ -224,7 +724,165 @@ mod tests {
         let result = game.place_stone(Position::new(1, 1), Color::White);
 
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Ko rule violation");
+        assert_eq!(result.unwrap_err(), MoveError::Ko(Position::new(1, 1)));
+    }
+
+    /// The ko from `test_ko_rule`, just taken by Black
+    fn ko_just_taken(ko: KoRule) -> Game {
+        let ruleset = Ruleset { ko, ..Ruleset::default() };
+        let mut game = game_from_rows(19, ruleset, &[
+            ".BW.",
+            "BW.W",
+            ".BW.",
+        ]);
+        game.place_stone(Position::new(2, 1), Color::Black).unwrap();
+        game
+    }
+
+    #[test]
+    fn test_pass_recorded_in_history() {
+        let mut game = ko_just_taken(KoRule::SituationalSuperko);
+        let hash = game.board.hash();
+
+        game.pass(Color::White).unwrap();
+        assert_eq!(game.history.last(), Some(&(hash, Color::Black)));
+    }
+
+    #[test]
+    fn test_ko_after_passes() {
+        // Both players passed and play resumed: the retake is no longer an immediate ko
+        let retake = |ko| {
+            let mut game = ko_just_taken(ko);
+            game.pass(Color::White).unwrap();
+            game.pass(Color::Black).unwrap();
+            game.resume(Color::Black).unwrap();
+            game.place_stone(Position::new(1, 1), Color::White)
+        };
+
+        assert!(retake(KoRule::Simple).is_ok());
+        assert_eq!(retake(KoRule::PositionalSuperko).unwrap_err(), MoveError::Superko(Position::new(1, 1)));
+    }
+
+    #[test]
+    fn test_capture_of_two_is_not_ko() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+
+        // White pair on the edge, Black takes both: nothing to retake
+        for (x, y) in [(0, 1), (0, 0), (1, 1), (1, 0), (8, 8)] {
+            game.place_stone(Position::new(x, y), game.get_turn()).unwrap();
+        }
+        game.pass(Color::White).unwrap();
+
+        let outcome = game.place_stone(Position::new(2, 0), Color::Black).unwrap();
+        assert_eq!(outcome.position, Position::new(2, 0));
+        assert_eq!(outcome.captures.len(), 2);
+        assert_eq!(outcome.ko, None);
+        assert_eq!(outcome.move_number, 7);
+    }
+
+    #[test]
+    fn test_events_for_capture_atari_and_ko() {
+        let mut game = Game::new();
+
+        // Same ko shape as above, Black to take at (2,1)
+        for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2), (2, 2), (10, 10), (3, 1)] {
+            game.place_stone(Position::new(x, y), game.get_turn()).unwrap();
+        }
+        game.take_events();
+
+        game.place_stone(Position::new(2, 1), Color::Black).unwrap();
+        let events = game.take_events();
+        assert_eq!(events[0], GameEvent::StonePlaced { color: Color::Black, position: Position::new(2, 1) });
+        assert_eq!(events[1], GameEvent::GroupCaptured { color: Color::White, size: 1 });
+
+        // White's (2,0) stone is down to its last liberty at (3,0)
+        let ataris = events.iter().filter(|e| matches!(e, GameEvent::Atari { .. })).count();
+        assert_eq!(ataris, 1);
+        assert_eq!(events.last(), Some(&GameEvent::KoStarted { point: Position::new(1, 1) }));
+        assert!(game.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_events_for_pass_and_end() {
+        let mut game = Game::new();
+        game.pass(Color::Black).unwrap();
+        game.resign(Color::White).unwrap();
+
+        let winner = Color::Black;
+        assert_eq!(game.take_events(), vec![
+            GameEvent::Pass { color: Color::Black },
+            GameEvent::GameEnded { result: GameResult::Resignation { winner } },
+        ]);
+    }
+
+    #[test]
+    fn test_large_group_formed_once() {
+        let mut game = Game::with_size(BoardSize::square(13).unwrap(), Ruleset::default());
+
+        // Black builds a wall along the top edge while White plays on the bottom
+        for x in 0..12 {
+            game.place_stone(Position::new(x, 0), Color::Black).unwrap();
+            game.place_stone(Position::new(x, 12), Color::White).unwrap();
+        }
+
+        let formed: Vec<_> = game.take_events().into_iter()
+            .filter_map(|e| match e {
+                GameEvent::LargeGroupFormed { group } => Some(group),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(formed.len(), 2);
+        assert_eq!((formed[0].color, formed[0].stones.len()), (Color::Black, 11));
+        assert_eq!((formed[1].color, formed[1].stones.len()), (Color::White, 11));
+    }
+
+    #[test]
+    fn test_legal_moves_exclude_ko_and_suicide() {
+        let mut game = Game::with_size(BoardSize::square(5).unwrap(), Ruleset::default());
+        assert_eq!(game.legal_moves().len(), 25);
+
+        // Ko shape from test_ko_rule, then Black takes at (2,1)
+        for (x, y) in [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2), (2, 2), (4, 4), (3, 1), (2, 1)] {
+            game.place_stone(Position::new(x, y), game.get_turn()).unwrap();
+        }
+
+        // White may not retake at (1,1), nor fill Black's eye at (0,0)
+        let legal = game.legal_moves();
+        assert!(!legal.contains(&Position::new(1, 1)));
+        assert!(!legal.contains(&Position::new(0, 0)));
+        assert!(legal.contains(&Position::new(3, 0)));
+        assert_eq!(legal.len(), 25 - 8 - 2);
+        for pos in legal {
+            assert!(game.clone().place_stone(pos, Color::White).is_ok());
+        }
+
+        game.pass(Color::White).unwrap();
+        game.pass(Color::Black).unwrap();
+        assert!(game.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn test_preview_move_leaves_game_untouched() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        game.place_stone(Position::new(0, 0), Color::Black).unwrap();
+        game.place_stone(Position::new(1, 0), Color::White).unwrap();
+        game.take_events();
+
+        // White's capture can be previewed on Black's turn
+        let preview = game.preview_move(Position::new(0, 1), Color::White).unwrap();
+        assert_eq!(preview.outcome.captures, vec![Position::new(0, 0)]);
+        assert_eq!(preview.board[0][0], None);
+        assert_eq!(preview.board[1][0], Some(Color::White));
+
+        assert_eq!(
+            game.preview_move(Position::new(1, 0), Color::Black).unwrap_err(),
+            MoveError::Occupied(Position::new(1, 0)),
+        );
+
+        assert_eq!(game.get_board()[0][0], Some(Color::Black));
+        assert_eq!(game.get_turn(), Color::Black);
+        assert_eq!(game.moves().len(), 2);
+        assert!(game.take_events().is_empty());
     }
 
     #[test]
@@ -243,7 +901,7 @@ mod tests {
         // White tries suicide at (1,1)
         let result = game.place_stone(Position::new(1, 1), Color::White);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Suicide move not allowed");
+        assert_eq!(result.unwrap_err(), MoveError::Suicide(Position::new(1, 1)));
     }
 
     #[test]
@@ -251,12 +909,50 @@ mod tests {
         let mut game = Game::new();
 
         assert_eq!(game.turn, Color::Black);
-        game.pass();
+        game.pass(Color::Black).unwrap();
         assert_eq!(game.turn, Color::White);
-        game.pass();
+        game.pass(Color::White).unwrap();
         assert_eq!(game.turn, Color::Black);
     }
 
+    #[test]
+    fn test_pass_turn_enforcement() {
+        let mut game = Game::new();
+
+        let result = game.pass(Color::White);
+        assert_eq!(result.unwrap_err(), MoveError::NotYourTurn);
+        assert_eq!(game.get_passes(), 0);
+    }
+
+    #[test]
+    fn test_two_passes_end_game() {
+        let mut game = Game::new();
+
+        game.pass(Color::Black).unwrap();
+        assert_eq!(game.get_passes(), 1);
+        assert_eq!(game.get_phase(), GamePhase::Playing);
+
+        game.pass(Color::White).unwrap();
+        assert_eq!(game.get_passes(), 2);
+        assert_eq!(game.get_phase(), GamePhase::Scoring);
+
+        let result = game.place_stone(Position::new(3, 3), Color::Black);
+        assert_eq!(result.unwrap_err(), MoveError::GameOver);
+        assert_eq!(game.pass(Color::Black).unwrap_err(), MoveError::GameOver);
+    }
+
+    #[test]
+    fn test_move_resets_pass_count() {
+        let mut game = Game::new();
+
+        game.pass(Color::Black).unwrap();
+        game.place_stone(Position::new(3, 3), Color::White).unwrap();
+        assert_eq!(game.get_passes(), 0);
+
+        game.pass(Color::Black).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Playing);
+    }
+
     #[test]
     fn test_reset() {
         let mut game = Game::new();
@@ -272,4 +968,335 @@ mod tests {
         assert!(game.board.is_empty(Position::new(3, 3)));
         assert!(game.board.is_empty(Position::new(4, 4)));
     }
+
+    /// Black wall on column 2 of a 5x5 board, lone white stone on the edge, both passed
+    fn scoring_game() -> Game {
+        let mut game = Game::with_size(BoardSize::square(5).unwrap(), Ruleset::default());
+        game.set_komi(0.5);
+        for y in 0..5 {
+            game.place_stone(Position::new(2, y), Color::Black).unwrap();
+            if y == 0 {
+                game.place_stone(Position::new(0, 1), Color::White).unwrap();
+            } else {
+                game.pass(Color::White).unwrap();
+            }
+        }
+        game.pass(Color::Black).unwrap();
+        game
+    }
+
+    #[test]
+    fn test_toggle_dead_marks_whole_group() {
+        let mut game = scoring_game();
+        assert_eq!(game.get_phase(), GamePhase::Scoring);
+
+        game.toggle_dead(Position::new(2, 3)).unwrap();
+        assert_eq!(game.get_dead_stones().len(), 5);
+
+        game.toggle_dead(Position::new(2, 0)).unwrap();
+        assert!(game.get_dead_stones().is_empty());
+
+        assert!(game.toggle_dead(Position::new(4, 4)).is_err());
+    }
+
+    #[test]
+    fn test_toggle_dead_requires_scoring_phase() {
+        let mut game = Game::new();
+        game.place_stone(Position::new(3, 3), Color::Black).unwrap();
+
+        let result = game.toggle_dead(Position::new(3, 3));
+        assert_eq!(result.unwrap_err(), "Not in scoring phase");
+    }
+
+    #[test]
+    fn test_accept_score_locks_result() {
+        let mut game = scoring_game();
+        game.toggle_dead(Position::new(0, 1)).unwrap();
+
+        game.accept_score(Color::Black).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Scoring);
+        assert!(game.get_final_score().is_none());
+
+        game.accept_score(Color::White).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Finished);
+        let score = game.get_final_score().unwrap();
+        assert_eq!(score.black.total, 25.0);
+        assert_eq!(score.white.total, 0.5);
+        assert_eq!(game.get_result(), Some(GameResult::Score { winner: Color::Black, margin: 24.5 }));
+
+        assert!(game.toggle_dead(Position::new(2, 2)).is_err());
+    }
+
+    #[test]
+    fn test_resign() {
+        let mut game = Game::new();
+        game.place_stone(Position::new(3, 3), Color::Black).unwrap();
+
+        // Either player may resign, not only the one to move
+        game.resign(Color::Black).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Finished);
+        assert_eq!(game.get_result(), Some(GameResult::Resignation { winner: Color::White }));
+        assert_eq!(game.moves().last().unwrap().kind, MoveKind::Resign);
+
+        assert_eq!(game.resign(Color::White).unwrap_err(), MoveError::GameOver);
+        assert_eq!(game.place_stone(Position::new(4, 4), Color::White).unwrap_err(), MoveError::GameOver);
+    }
+
+    #[test]
+    fn test_resign_while_scoring() {
+        let mut game = scoring_game();
+        game.resign(Color::White).unwrap();
+
+        assert_eq!(game.get_result().unwrap().to_string(), "B+R");
+        assert!(game.get_final_score().is_none());
+    }
+
+    #[test]
+    fn test_marking_change_clears_acceptance() {
+        let mut game = scoring_game();
+
+        game.accept_score(Color::Black).unwrap();
+        game.toggle_dead(Position::new(0, 1)).unwrap();
+        assert_eq!(game.get_score_accepted(), (false, false));
+
+        game.accept_score(Color::White).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Scoring);
+    }
+
+    #[test]
+    fn test_resume_play() {
+        let mut game = scoring_game();
+        game.toggle_dead(Position::new(0, 1)).unwrap();
+
+        game.resume(Color::White).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Playing);
+        assert_eq!(game.get_turn(), Color::Black);
+        assert_eq!(game.get_passes(), 0);
+        assert!(game.get_dead_stones().is_empty());
+
+        game.place_stone(Position::new(1, 0), Color::Black).unwrap();
+    }
+
+    #[test]
+    fn test_suicide_allowed_by_ruleset() {
+        let mut game = Game::with_size(BoardSize::square(19).unwrap(), Ruleset::preset(RulesetName::NewZealand));
+
+        // Two white stones in the corner, surrounded by black except one point
+        //   0 1 2
+        // 0 W W B
+        // 1 . B .
+        // 2 B . .
+        game.place_stone(Position::new(2, 0), Color::Black).unwrap();
+        game.place_stone(Position::new(0, 0), Color::White).unwrap();
+        game.place_stone(Position::new(1, 1), Color::Black).unwrap();
+        game.place_stone(Position::new(1, 0), Color::White).unwrap();
+        game.place_stone(Position::new(0, 2), Color::Black).unwrap();
+
+        // White fills its own last liberty: the whole group is removed
+        game.place_stone(Position::new(0, 1), Color::White).unwrap();
+
+        assert!(game.board.is_empty(Position::new(0, 0)));
+        assert!(game.board.is_empty(Position::new(1, 0)));
+        assert!(game.board.is_empty(Position::new(0, 1)));
+        assert_eq!(game.prisoners, (0, 3));
+        assert_eq!(game.moves().last().unwrap().self_captures.len(), 3);
+
+        // Replay reproduces the suicide in both directions
+        let mut replay = game.replay();
+        replay.goto(6);
+        assert_eq!(replay.board(), game.get_board());
+        replay.back();
+        assert_eq!(replay.board()[0][0], Some(Color::White));
+        assert_eq!(replay.board()[1][0], None);
+    }
+
+    #[test]
+    fn test_score_uses_ruleset() {
+        let mut game = Game::with_size(BoardSize::square(5).unwrap(), Ruleset::preset(RulesetName::Japanese));
+        game.pass(Color::Black).unwrap();
+        game.pass(Color::White).unwrap();
+
+        let score = game.score();
+        assert_eq!(score.method, ScoringMethod::Territory);
+        assert_eq!(score.white.komi, 6.5);
+    }
+
+    #[test]
+    fn test_handicap_compensation_added_to_komi() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::preset(RulesetName::Aga));
+        game.set_handicap(3);
+
+        assert_eq!(game.score().white.komi, 9.5);
+    }
+
+    /// Build a game from a picture of the board ('B', 'W', '.'), Black to move
+    fn game_from_rows(size: usize, ruleset: Ruleset, rows: &[&str]) -> Game {
+        let mut game = Game::with_size(BoardSize::square(size).unwrap(), ruleset);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let color = match c {
+                    'B' => Some(Color::Black),
+                    'W' => Some(Color::White),
+                    _ => None,
+                };
+                game.setup_stone(Position::new(x, y), color).unwrap();
+            }
+        }
+        game
+    }
+
+    /// Three kos on one board: White holds the first two, Black the third
+    fn triple_ko_game(ko: KoRule) -> Game {
+        let ruleset = Ruleset { ko, ..Ruleset::default() };
+        game_from_rows(19, ruleset, &[
+            ".BW.",
+            "BW.W",
+            ".BW.",
+            "....",
+            ".BW.",
+            "BW.W",
+            ".BW.",
+            "....",
+            ".BW.",
+            "B.BW",
+            ".BW.",
+        ])
+    }
+
+    /// The six ko captures that bring the triple ko back to its starting position
+    const TRIPLE_KO_CYCLE: [(usize, usize, Color); 6] = [
+        (2, 1, Color::Black), // Black takes the first ko
+        (1, 9, Color::White), // White takes the third
+        (2, 5, Color::Black), // Black takes the second
+        (1, 1, Color::White), // White retakes the first
+        (2, 9, Color::Black), // Black retakes the third
+        (1, 5, Color::White), // White retakes the second: repeats the start
+    ];
+
+    #[test]
+    fn test_triple_ko_cycles_under_simple_ko() {
+        let mut game = triple_ko_game(KoRule::Simple);
+        let start = game.get_board();
+
+        for (x, y, color) in TRIPLE_KO_CYCLE {
+            game.place_stone(Position::new(x, y), color).unwrap();
+        }
+
+        assert_eq!(game.get_board(), start);
+    }
+
+    #[test]
+    fn test_triple_ko_blocked_by_superko() {
+        for ko in [KoRule::PositionalSuperko, KoRule::SituationalSuperko] {
+            let mut game = triple_ko_game(ko);
+
+            for &(x, y, color) in &TRIPLE_KO_CYCLE[..5] {
+                game.place_stone(Position::new(x, y), color).unwrap();
+            }
+
+            let (x, y, color) = TRIPLE_KO_CYCLE[5];
+            let result = game.place_stone(Position::new(x, y), color);
+            assert_eq!(result.unwrap_err(), MoveError::Superko(Position::new(x, y)), "{:?} allowed the triple ko to repeat", ko);
+            assert_eq!(game.get_turn(), Color::White);
+        }
+    }
+
+    /// Black sends two stones at (2,0); White takes them at (4,0); Black returns one at (3,0)
+    fn sending_two_returning_one(ko: KoRule) -> Game {
+        let ruleset = Ruleset { ko, ..Ruleset::default() };
+        game_from_rows(5, ruleset, &[
+            "BW.B.",
+            "BWWWB",
+            "B..WB",
+            ".WBB.",
+            "BW.WW",
+        ])
+    }
+
+    #[test]
+    fn test_sending_two_returning_one_positional_superko() {
+        let mut game = sending_two_returning_one(KoRule::PositionalSuperko);
+
+        game.place_stone(Position::new(2, 0), Color::Black).unwrap();
+        game.place_stone(Position::new(4, 0), Color::White).unwrap();
+        assert_eq!(game.moves().last().unwrap().captures.len(), 2);
+
+        // Returning one stone recreates the starting board
+        let result = game.place_stone(Position::new(3, 0), Color::Black);
+        assert_eq!(result.unwrap_err(), MoveError::Superko(Position::new(3, 0)));
+    }
+
+    #[test]
+    fn test_sending_two_returning_one_situational_superko() {
+        let mut game = sending_two_returning_one(KoRule::SituationalSuperko);
+        let start = game.get_board();
+
+        game.place_stone(Position::new(2, 0), Color::Black).unwrap();
+        game.place_stone(Position::new(4, 0), Color::White).unwrap();
+
+        // Same board as the start, but now White is to move: a new situation
+        game.place_stone(Position::new(3, 0), Color::Black).unwrap();
+        assert_eq!(game.get_board(), start);
+        assert_eq!(game.get_turn(), Color::White);
+    }
+
+    #[test]
+    fn test_fixed_handicap() {
+        let mut game = Game::new();
+        game.setup_handicap(4, HandicapPlacement::Fixed).unwrap();
+
+        assert_eq!(game.get_handicap(), 4);
+        assert_eq!(game.get_setup().len(), 4);
+        assert_eq!(game.board.get(Position::new(3, 3)), Some(Color::Black));
+        assert_eq!(game.board.get(Position::new(15, 15)), Some(Color::Black));
+        assert_eq!(game.get_turn(), Color::White);
+
+        // White moves first
+        assert!(game.place_stone(Position::new(10, 10), Color::Black).is_err());
+        game.place_stone(Position::new(10, 10), Color::White).unwrap();
+        assert!(game.moves().len() == 1);
+    }
+
+    #[test]
+    fn test_free_handicap() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        game.setup_handicap(2, HandicapPlacement::Free).unwrap();
+
+        assert_eq!(game.get_turn(), Color::Black);
+        assert_eq!(game.pass(Color::Black).unwrap_err(), MoveError::HandicapPending);
+
+        game.place_stone(Position::new(4, 4), Color::Black).unwrap();
+        assert_eq!(game.get_handicap_to_place(), 1);
+        assert_eq!(game.get_turn(), Color::Black);
+
+        game.place_stone(Position::new(2, 2), Color::Black).unwrap();
+        assert_eq!(game.get_handicap_to_place(), 0);
+        assert_eq!(game.get_turn(), Color::White);
+
+        // Handicap stones are setup, not moves
+        assert!(game.moves().is_empty());
+        assert_eq!(game.get_setup().len(), 2);
+
+        game.place_stone(Position::new(6, 6), Color::White).unwrap();
+        assert_eq!(game.moves().len(), 1);
+    }
+
+    #[test]
+    fn test_handicap_validation() {
+        let mut game = Game::new();
+        assert!(game.setup_handicap(1, HandicapPlacement::Fixed).is_err());
+        assert!(game.setup_handicap(10, HandicapPlacement::Free).is_err());
+
+        let mut odd_size = Game::with_size(BoardSize::square(15).unwrap(), Ruleset::default());
+        assert!(odd_size.setup_handicap(4, HandicapPlacement::Fixed).is_err());
+        odd_size.setup_handicap(4, HandicapPlacement::Free).unwrap();
+
+        let mut started = Game::new();
+        started.place_stone(Position::new(3, 3), Color::Black).unwrap();
+        assert!(started.setup_handicap(2, HandicapPlacement::Fixed).is_err());
+
+        let mut even = Game::new();
+        even.setup_handicap(0, HandicapPlacement::Fixed).unwrap();
+        assert_eq!(even.get_turn(), Color::Black);
+    }
 }
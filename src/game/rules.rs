@@ -1,117 +1,223 @@
-use super::board::Board;
-use super::types::{Color, Position};
+use super::board::{Bits, Board};
+use super::error::MoveError;
+use super::history::{Move, MoveKind};
+use super::ruleset::{KoRule, Ruleset};
+use super::types::{Color, PlayerScore, Position, Score, ScoringMethod};
+use std::collections::HashSet;
 
 /// Check if placing a stone would be suicide (illegal unless it captures)
 pub fn is_suicide(board: &Board, pos: Position, color: Color) -> bool {
-    // Create a temporary board to test the move
-    let mut test_board = Board {
-        size: board.size(),
-        grid: board.grid.clone(),
-    };
+    let stone = board.point(pos);
 
-    // Place the stone
-    test_board.set(pos, Some(color));
+    // An empty neighbor is a liberty
+    if !(board.neighbors(stone) & board.empty()).is_empty() {
+        return false;
+    }
 
-    // Check if this move captures any opponent stones
-    let opponent_color = color.opposite();
-    let captures = find_captures(&test_board, opponent_color);
+    for adjacent in pos.adjacent(board.size()) {
+        let Some(chain) = board.chain_at(adjacent) else {
+            continue;
+        };
 
-    // If we capture opponent stones, remove them
-    if !captures.is_empty() {
-        for capture_pos in captures {
-            test_board.set(capture_pos, None);
+        if chain.color == color {
+            // Joining a friendly group that keeps another liberty
+            if !(chain.liberties & !stone).is_empty() {
+                return false;
+            }
+        } else if chain.liberties == stone {
+            // Capturing an opponent group frees a liberty
+            return false;
         }
     }
 
-    // Now check if our group has liberties
-    // If it has zero liberties, it's suicide
-    test_board.count_liberties(pos) == 0
+    true
 }
 
-/// Detect and return positions of captured stones (groups with 0 liberties)
-pub fn find_captures(board: &Board, opponent_color: Color) -> Vec<Position> {
-    use std::collections::HashSet;
+/// Detect and return positions of opponent stones captured by a stone just played at `pos`
+///
+/// Only groups touching `pos` can have lost their last liberty, so nothing else is checked.
+pub fn find_captures(board: &Board, pos: Position, opponent_color: Color) -> Vec<Position> {
+    let mut captured = Bits::default();
 
-    let mut captured = Vec::new();
-    let mut checked_groups: HashSet<Position> = HashSet::new();
+    for adjacent in pos.adjacent(board.size()) {
+        if let Some(chain) = board.chain_at(adjacent) {
+            if chain.color == opponent_color && chain.liberties.is_empty() {
+                captured = captured | chain.stones;
+            }
+        }
+    }
 
-    let size = board.size();
+    board.positions(captured)
+}
 
-    // Scan board for opponent stones
-    for y in 0..size {
-        for x in 0..size {
-            let pos = Position::new(x, y);
+/// Play a stone on an empty point: remove captures and apply the suicide and ko rules
+///
+/// `history` is as for `is_ko_violation`. On success the board holds the new position;
+/// an illegal move leaves it as it was.
+pub fn try_play(
+    board: &mut Board,
+    ruleset: &Ruleset,
+    history: &[(u64, Color)],
+    pos: Position,
+    color: Color,
+) -> Result<Move, MoveError> {
+    if !ruleset.suicide_allowed && is_suicide(board, pos, color) {
+        return Err(MoveError::Suicide(pos));
+    }
 
-            // Skip if already checked as part of another group
-            if checked_groups.contains(&pos) {
-                continue;
-            }
+    board.set(pos, Some(color));
 
-            // Check if this is an opponent stone
-            if board.get(pos) == Some(opponent_color) {
-                let group = board.find_group(pos);
+    let opponent_color = color.opposite();
+    let captures = find_captures(board, pos, opponent_color);
+    board.remove_stones(&captures);
+
+    // Where suicide is allowed, a group left without liberties removes itself
+    let mut self_captures = Vec::new();
+    if ruleset.suicide_allowed && board.count_liberties(pos) == 0 {
+        self_captures.extend(board.find_group(pos));
+        board.remove_stones(&self_captures);
+    }
 
-                // Mark all positions in this group as checked
-                for &group_pos in &group {
-                    checked_groups.insert(group_pos);
-                }
+    let mv = Move {
+        color,
+        kind: MoveKind::Stone(pos),
+        captures,
+        self_captures,
+    };
 
-                // If group has no liberties, it's captured
-                if board.count_liberties(pos) == 0 {
-                    captured.extend(group);
-                }
-            }
-        }
+    let board_hash = board.hash();
+    if is_ko_violation(ruleset.ko, board_hash, opponent_color, history) {
+        // Take the move back
+        board.undo(&mv);
+
+        // Recreating the position before the opponent's last move is a plain ko;
+        // anything older is a superko repetition
+        let immediate = recreates_previous(board_hash, history);
+        return Err(if immediate { MoveError::Ko(pos) } else { MoveError::Superko(pos) });
     }
 
-    captured
+    Ok(mv)
 }
 
 /// Check if a move violates the ko rule
-pub fn is_ko_violation(board_hash: u64, history: &[u64]) -> bool {
-    // Simple ko: check if the position matches any recent position
-    // For proper ko detection, we check the last few moves
-    // Typically, ko occurs when recapturing immediately (2 moves back)
-    if history.is_empty() {
-        return false;
+///
+/// `history` holds the board hash after each earlier move or pass and the player to move
+/// next; `next_to_move` is the player to move after the move being checked.
+pub fn is_ko_violation(ko: KoRule, board_hash: u64, next_to_move: Color, history: &[(u64, Color)]) -> bool {
+    match ko {
+        KoRule::Simple => recreates_previous(board_hash, history),
+        KoRule::PositionalSuperko => history.iter().any(|&(hash, _)| hash == board_hash),
+        KoRule::SituationalSuperko => history.contains(&(board_hash, next_to_move)),
     }
+}
 
-    // Check if this board state appeared in recent history
-    // For simple ko, checking last 2-3 moves is sufficient
-    let check_depth = history.len().min(3);
-    let recent_history = &history[history.len() - check_depth..];
-
-    recent_history.contains(&board_hash)
+/// Whether a move brings back the position from before the opponent's last move (or pass)
+fn recreates_previous(board_hash: u64, history: &[(u64, Color)]) -> bool {
+    history.iter().rev().nth(1).is_some_and(|&(hash, _)| hash == board_hash)
 }
 
-/// Calculate a hash of the board position (for ko detection)
-pub fn hash_board(board: &Board) -> u64 {
-    let mut hash: u64 = 0;
+/// Count the score with dead stones removed
+///
+/// Area scoring counts living stones plus territory; territory scoring counts
+/// territory plus prisoners (stones captured during play and dead stones).
+/// Empty regions touching both colors are neutral (dame). Komi goes to White.
+pub fn score(
+    board: &Board,
+    dead_stones: &HashSet<Position>,
+    prisoners: (u32, u32),
+    komi: f32,
+    method: ScoringMethod,
+) -> Score {
+    let size = board.size();
+    let mut stones = (0, 0);     // Living stones (black, white)
+    let mut dead = (0, 0);       // Dead stones (black, white)
+
+    for y in 0..size.height() {
+        for x in 0..size.width() {
+            let pos = Position::new(x, y);
+            let counter = if dead_stones.contains(&pos) { &mut dead } else { &mut stones };
+            match board.get(pos) {
+                Some(Color::Black) => counter.0 += 1,
+                Some(Color::White) => counter.1 += 1,
+                None => {}
+            }
+        }
+    }
+
+    let (black_territory, white_territory) = territory(board, dead_stones);
+    let mut black = PlayerScore { territory: black_territory.len() as u32, ..Default::default() };
+    let mut white = PlayerScore { territory: white_territory.len() as u32, komi, ..Default::default() };
+
+    match method {
+        ScoringMethod::Area => {
+            black.stones = stones.0;
+            white.stones = stones.1;
+        }
+        ScoringMethod::Territory => {
+            // Each side keeps the opponent stones it captured or killed
+            black.captures = prisoners.1 + dead.1;
+            white.captures = prisoners.0 + dead.0;
+        }
+    }
 
+    for player in [&mut black, &mut white] {
+        player.total = (player.territory + player.stones + player.captures) as f32 + player.komi;
+    }
+
+    Score { method, black, white }
+}
+
+/// Points surrounded by only one color (black, white), dead stones counting as empty
+pub fn territory(board: &Board, dead_stones: &HashSet<Position>) -> (Vec<Position>, Vec<Position>) {
     let size = board.size();
+    let mut territory = (Vec::new(), Vec::new());
+    let mut visited: HashSet<Position> = HashSet::new();
+
+    let is_alive = |pos: Position| board.get(pos).filter(|_| !dead_stones.contains(&pos));
 
-    for y in 0..size {
-        for x in 0..size {
+    for y in 0..size.height() {
+        for x in 0..size.width() {
             let pos = Position::new(x, y);
-            if let Some(color) = board.get(pos) {
-                // XOR with a unique value for each (position, color) pair
-                // Use prime numbers to minimize collisions
-                let position_hash = (x as u64 * 19 + y as u64) * 31;
-                let color_hash = match color {
-                    Color::Black => 1,
-                    Color::White => 2,
-                };
-                hash ^= position_hash.wrapping_mul(color_hash);
+            if is_alive(pos).is_some() || visited.contains(&pos) {
+                continue;
+            }
+
+            // Flood fill the region of empty (or dead) points
+            let mut region = Vec::new();
+            let mut borders = (false, false); // (black, white)
+            let mut to_visit = vec![pos];
+            visited.insert(pos);
+
+            while let Some(current) = to_visit.pop() {
+                region.push(current);
+                for adjacent in current.adjacent(size) {
+                    match is_alive(adjacent) {
+                        Some(Color::Black) => borders.0 = true,
+                        Some(Color::White) => borders.1 = true,
+                        None => {
+                            if visited.insert(adjacent) {
+                                to_visit.push(adjacent);
+                            }
+                        }
+                    }
+                }
+            }
+
+            match borders {
+                (true, false) => territory.0.extend(region),
+                (false, true) => territory.1.extend(region),
+                _ => {} // Dame or empty board
             }
         }
     }
 
-    hash
+    territory
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::game::BoardSize;
 
     #[test]
     fn test_basic_capture_single_stone() {
@@ -124,7 +230,7 @@ mod tests {
         board.set(Position::new(1, 0), Some(Color::White));
         board.set(Position::new(1, 2), Some(Color::White));
 
-        let captures = find_captures(&board, Color::Black);
+        let captures = find_captures(&board, Position::new(1, 2), Color::Black);
         assert_eq!(captures.len(), 1);
         assert!(captures.contains(&Position::new(1, 1)));
     }
@@ -145,7 +251,7 @@ mod tests {
         board.set(Position::new(1, 2), Some(Color::White));
         board.set(Position::new(2, 2), Some(Color::White));
 
-        let captures = find_captures(&board, Color::Black);
+        let captures = find_captures(&board, Position::new(2, 2), Color::Black);
         assert_eq!(captures.len(), 2);
         assert!(captures.contains(&Position::new(1, 1)));
         assert!(captures.contains(&Position::new(2, 1)));
@@ -225,20 +331,25 @@ mod tests {
         board.set(Position::new(1, 2), Some(Color::Black));
         board.set(Position::new(0, 1), Some(Color::White));
 
-        let hash1 = hash_board(&board);
+        let hash1 = board.hash();
 
         // White captures at (1,1)
         board.set(Position::new(1, 1), Some(Color::White));
-        let hash2 = hash_board(&board);
+        let hash2 = board.hash();
 
-        // History contains the position before white's move
-        let history = vec![hash1];
+        // History holds the position before and after white's move
+        let history = vec![(hash1, Color::White), (hash2, Color::Black)];
 
         // Now black tries to recapture immediately
         // This would create the same board position as before
         // So it should be detected as ko violation
-        assert!(is_ko_violation(hash1, &history));
-        assert!(!is_ko_violation(hash2, &history));
+        assert!(is_ko_violation(KoRule::Simple, hash1, Color::White, &history));
+        assert!(!is_ko_violation(KoRule::Simple, hash2, Color::White, &history));
+
+        // Superko variants look at the whole history
+        assert!(is_ko_violation(KoRule::SituationalSuperko, hash1, Color::White, &history));
+        assert!(!is_ko_violation(KoRule::SituationalSuperko, hash1, Color::Black, &history));
+        assert!(is_ko_violation(KoRule::PositionalSuperko, hash1, Color::Black, &history));
     }
 
     #[test]
@@ -280,7 +391,7 @@ mod tests {
 
         // White group is connected: (2,0)-(1,1)-(2,1)-(3,1) and (2,1)-(2,2)
         // Verify white has liberties before capture
-        let captures = find_captures(&board, Color::White);
+        let captures = find_captures(&board, Position::new(1, 2), Color::White);
         assert!(captures.is_empty());
 
         // Black surrounds completely
@@ -290,7 +401,138 @@ mod tests {
         board.set(Position::new(2, 3), Some(Color::Black)); // Close the last liberty
 
         // Now the entire white group should be captured
-        let captures_after = find_captures(&board, Color::White);
+        let captures_after = find_captures(&board, Position::new(2, 3), Color::White);
         assert_eq!(captures_after.len(), 5); // All 5 white stones captured
     }
+
+    fn dead(points: &[(usize, usize)]) -> HashSet<Position> {
+        points.iter().map(|&(x, y)| Position::new(x, y)).collect()
+    }
+
+    #[test]
+    fn test_score_area_split_board() {
+        let mut board = Board::with_size(BoardSize::square(5).unwrap());
+
+        // Black wall on column 1, white wall on column 3; column 2 is dame
+        for y in 0..5 {
+            board.set(Position::new(1, y), Some(Color::Black));
+            board.set(Position::new(3, y), Some(Color::White));
+        }
+
+        let score = score(&board, &HashSet::new(), (0, 0), 0.5, ScoringMethod::Area);
+        assert_eq!(score.black, PlayerScore { territory: 5, stones: 5, captures: 0, komi: 0.0, total: 10.0 });
+        assert_eq!(score.white, PlayerScore { territory: 5, stones: 5, captures: 0, komi: 0.5, total: 10.5 });
+        assert_eq!(score.margin(), -0.5);
+    }
+
+    #[test]
+    fn test_score_territory_split_board() {
+        let mut board = Board::with_size(BoardSize::square(5).unwrap());
+
+        for y in 0..5 {
+            board.set(Position::new(1, y), Some(Color::Black));
+            board.set(Position::new(3, y), Some(Color::White));
+        }
+
+        let score = score(&board, &HashSet::new(), (2, 3), 6.5, ScoringMethod::Territory);
+        assert_eq!(score.black, PlayerScore { territory: 5, stones: 0, captures: 3, komi: 0.0, total: 8.0 });
+        assert_eq!(score.white, PlayerScore { territory: 5, stones: 0, captures: 2, komi: 6.5, total: 13.5 });
+    }
+
+    #[test]
+    fn test_score_dead_stones_count_for_opponent() {
+        let mut board = Board::with_size(BoardSize::square(5).unwrap());
+
+        for y in 0..5 {
+            board.set(Position::new(2, y), Some(Color::Black));
+        }
+        board.set(Position::new(0, 0), Some(Color::White)); // Dead invader
+
+        let alive = score(&board, &HashSet::new(), (0, 0), 0.0, ScoringMethod::Area);
+        assert_eq!(alive.black.total, 15.0); // Left side touches white: neutral
+        assert_eq!(alive.white.total, 1.0);
+
+        let area = score(&board, &dead(&[(0, 0)]), (0, 0), 0.0, ScoringMethod::Area);
+        assert_eq!(area.black.total, 25.0);
+        assert_eq!(area.white.total, 0.0);
+
+        // Under territory scoring the dead stone is also a prisoner
+        let territory = score(&board, &dead(&[(0, 0)]), (0, 0), 0.0, ScoringMethod::Territory);
+        assert_eq!(territory.black.territory, 20);
+        assert_eq!(territory.black.captures, 1);
+        assert_eq!(territory.black.total, 21.0);
+    }
+
+    #[test]
+    fn test_score_dame_between_groups() {
+        let mut board = Board::with_size(BoardSize::square(3).unwrap());
+
+        // B . W
+        // B . W
+        // B . W
+        for y in 0..3 {
+            board.set(Position::new(0, y), Some(Color::Black));
+            board.set(Position::new(2, y), Some(Color::White));
+        }
+
+        let score = score(&board, &HashSet::new(), (0, 0), 0.0, ScoringMethod::Territory);
+        assert_eq!(score.black.territory, 0);
+        assert_eq!(score.white.territory, 0);
+    }
+
+    #[test]
+    fn test_score_empty_board() {
+        let board = Board::with_size(BoardSize::square(9).unwrap());
+        let score = score(&board, &HashSet::new(), (0, 0), 7.5, ScoringMethod::Area);
+        assert_eq!(score.black.total, 0.0);
+        assert_eq!(score.white.total, 7.5);
+    }
+
+    #[test]
+    fn test_zobrist_hash_is_incremental() {
+        let mut board = Board::with_size(BoardSize::square(9).unwrap());
+        assert_eq!(board.hash(), 0);
+
+        board.set(Position::new(2, 3), Some(Color::Black));
+        board.set(Position::new(4, 4), Some(Color::White));
+        let hash = board.hash();
+
+        // Order of placement does not matter
+        let mut other = Board::with_size(BoardSize::square(9).unwrap());
+        other.set(Position::new(4, 4), Some(Color::White));
+        other.set(Position::new(2, 3), Some(Color::Black));
+        assert_eq!(other.hash(), hash);
+
+        // Replacing and removing stones updates the hash
+        board.set(Position::new(2, 3), Some(Color::White));
+        assert_ne!(board.hash(), hash);
+        board.set(Position::new(2, 3), None);
+        board.set(Position::new(4, 4), None);
+        assert_eq!(board.hash(), 0);
+    }
+
+    #[test]
+    fn test_zobrist_hash_distinguishes_old_collisions() {
+        // The previous XOR hash mapped all of these to 0
+        let mut corner_black = Board::new();
+        corner_black.set(Position::new(0, 0), Some(Color::Black));
+        let mut corner_white = Board::new();
+        corner_white.set(Position::new(0, 0), Some(Color::White));
+
+        assert_ne!(corner_black.hash(), 0);
+        assert_ne!(corner_white.hash(), 0);
+        assert_ne!(corner_black.hash(), corner_white.hash());
+
+        // Every single-stone position on a 19x19 board hashes uniquely
+        let mut seen = HashSet::new();
+        for y in 0..19 {
+            for x in 0..19 {
+                for color in [Color::Black, Color::White] {
+                    let mut board = Board::new();
+                    board.set(Position::new(x, y), Some(color));
+                    assert!(seen.insert(board.hash()));
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use super::types::ScoringMethod;
+
+/// Which earlier positions a move may not recreate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KoRule {
+    Simple,             // Only the immediate recapture is forbidden
+    PositionalSuperko,  // No earlier board position may repeat
+    SituationalSuperko, // No earlier position may repeat with the same player to move
+}
+
+/// Extra points White receives in handicap games (area scoring)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandicapCompensation {
+    None,
+    N,         // One point per handicap stone
+    NMinusOne, // One point per handicap stone after the first
+}
+
+/// Named rule sets players can choose from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RulesetName {
+    Japanese,
+    Chinese,
+    Aga,
+    Korean,
+    NewZealand,
+    TrompTaylor,
+    #[default]
+    Custom,
+}
+
+/// Rules a game is played under
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Ruleset {
+    #[serde(default)]
+    pub name: RulesetName,
+    pub komi: f32,
+    pub suicide_allowed: bool,
+    pub ko: KoRule,
+    pub scoring: ScoringMethod,
+    pub handicap_compensation: HandicapCompensation,
+}
+
+impl Ruleset {
+    /// Standard settings for a named rule set (`Custom` starts from Chinese)
+    pub fn preset(name: RulesetName) -> Self {
+        let (komi, suicide_allowed, ko, scoring, handicap_compensation) = match name {
+            RulesetName::Japanese | RulesetName::Korean => {
+                (6.5, false, KoRule::Simple, ScoringMethod::Territory, HandicapCompensation::None)
+            }
+            RulesetName::Chinese | RulesetName::Custom => {
+                (7.5, false, KoRule::PositionalSuperko, ScoringMethod::Area, HandicapCompensation::N)
+            }
+            RulesetName::Aga => {
+                (7.5, false, KoRule::SituationalSuperko, ScoringMethod::Area, HandicapCompensation::NMinusOne)
+            }
+            RulesetName::NewZealand => {
+                (7.0, true, KoRule::SituationalSuperko, ScoringMethod::Area, HandicapCompensation::None)
+            }
+            RulesetName::TrompTaylor => {
+                (7.5, true, KoRule::PositionalSuperko, ScoringMethod::Area, HandicapCompensation::None)
+            }
+        };
+
+        Self { name, komi, suicide_allowed, ko, scoring, handicap_compensation }
+    }
+
+    /// Same as the named preset apart from komi
+    pub fn is_preset(&self) -> bool {
+        self.name != RulesetName::Custom
+            && Self { komi: self.komi, ..Self::preset(self.name) } == *self
+    }
+
+    /// Points White receives for Black's handicap stones
+    pub fn handicap_bonus(&self, handicap: u8) -> f32 {
+        match self.handicap_compensation {
+            HandicapCompensation::None => 0.0,
+            HandicapCompensation::N => handicap as f32,
+            HandicapCompensation::NMinusOne => handicap.saturating_sub(1) as f32,
+        }
+    }
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::preset(RulesetName::Chinese)
+    }
+}
+
+/// A ruleset as chosen by a client: a preset name or a full custom definition
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+pub enum RulesetChoice {
+    Preset(RulesetName),
+    Custom(Ruleset),
+}
+
+impl RulesetChoice {
+    pub fn into_ruleset(self) -> Ruleset {
+        match self {
+            RulesetChoice::Preset(name) => Ruleset::preset(name),
+            RulesetChoice::Custom(ruleset) => ruleset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        let japanese = Ruleset::preset(RulesetName::Japanese);
+        assert_eq!(japanese.komi, 6.5);
+        assert_eq!(japanese.scoring, ScoringMethod::Territory);
+        assert_eq!(japanese.ko, KoRule::Simple);
+
+        let nz = Ruleset::preset(RulesetName::NewZealand);
+        assert!(nz.suicide_allowed);
+        assert_eq!(nz.komi, 7.0);
+
+        assert_eq!(Ruleset::default().name, RulesetName::Chinese);
+    }
+
+    #[test]
+    fn test_is_preset_ignores_komi() {
+        let mut rules = Ruleset::preset(RulesetName::Aga);
+        rules.komi = 0.5;
+        assert!(rules.is_preset());
+
+        rules.suicide_allowed = true;
+        assert!(!rules.is_preset());
+
+        assert!(!Ruleset::preset(RulesetName::Custom).is_preset());
+    }
+
+    #[test]
+    fn test_handicap_bonus() {
+        assert_eq!(Ruleset::preset(RulesetName::Chinese).handicap_bonus(4), 4.0);
+        assert_eq!(Ruleset::preset(RulesetName::Aga).handicap_bonus(4), 3.0);
+        assert_eq!(Ruleset::preset(RulesetName::Japanese).handicap_bonus(4), 0.0);
+        assert_eq!(Ruleset::preset(RulesetName::Aga).handicap_bonus(0), 0.0);
+    }
+
+    #[test]
+    fn test_choice_deserialization() {
+        let preset: RulesetChoice = serde_json::from_str("\"new_zealand\"").unwrap();
+        assert_eq!(preset.into_ruleset(), Ruleset::preset(RulesetName::NewZealand));
+
+        let custom: RulesetChoice = serde_json::from_str(
+            r#"{"komi": 0.5, "suicide_allowed": true, "ko": "simple",
+                "scoring": "territory", "handicap_compensation": "none"}"#,
+        ).unwrap();
+        let custom = custom.into_ruleset();
+        assert_eq!(custom.name, RulesetName::Custom);
+        assert_eq!(custom.komi, 0.5);
+        assert!(custom.suicide_allowed);
+    }
+}
@@ -16,7 +16,183 @@ impl Color {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Stage of the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GamePhase {
+    Playing,
+    Scoring,  // Both players passed; marking dead stones
+    Finished, // Score agreed, resignation or time out
+}
+
+/// How the final score is counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoringMethod {
+    Area,      // Chinese/AGA: stones + territory
+    Territory, // Japanese/Korean: territory + prisoners
+}
+
+/// One player's points, broken down by source
+///
+/// Only the parts counted by the scoring method are filled in, so they always add up to `total`.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct PlayerScore {
+    pub territory: u32,
+    pub stones: u32,
+    pub captures: u32,
+    pub komi: f32,
+    pub total: f32,
+}
+
+/// Score breakdown for both players
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Score {
+    pub method: ScoringMethod,
+    pub black: PlayerScore,
+    pub white: PlayerScore,
+}
+
+impl Score {
+    /// Black's total minus White's total (negative when White leads)
+    pub fn margin(&self) -> f32 {
+        self.black.total - self.white.total
+    }
+}
+
+/// How a finished game was decided
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum GameResult {
+    Resignation { winner: Color },
+    Score { winner: Color, margin: f32 },
+    Time { winner: Color },
+    Forfeit { winner: Color },
+    Jigo, // Equal scores
+}
+
+impl GameResult {
+    /// Result of a counted game from Black's margin (negative when White wins)
+    pub fn from_margin(margin: f32) -> Self {
+        if margin > 0.0 {
+            GameResult::Score { winner: Color::Black, margin }
+        } else if margin < 0.0 {
+            GameResult::Score { winner: Color::White, margin: -margin }
+        } else {
+            GameResult::Jigo
+        }
+    }
+}
+
+/// SGF `RE` notation: "B+R", "W+3.5", "B+T", "W+F", or "0" for jigo
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tag = |color: Color| match color {
+            Color::Black => "B",
+            Color::White => "W",
+        };
+
+        match *self {
+            GameResult::Resignation { winner } => write!(f, "{}+R", tag(winner)),
+            GameResult::Score { winner, margin } => write!(f, "{}+{}", tag(winner), margin),
+            GameResult::Time { winner } => write!(f, "{}+T", tag(winner)),
+            GameResult::Forfeit { winner } => write!(f, "{}+F", tag(winner)),
+            GameResult::Jigo => f.write_str("0"),
+        }
+    }
+}
+
+/// Smallest board dimension a game can be played on
+pub const MIN_BOARD_SIZE: usize = 2;
+/// Largest board dimension a game can be played on (SGF and GTP coordinates stop at 25)
+pub const MAX_BOARD_SIZE: usize = 25;
+
+/// Width and height of a board, each within MIN_BOARD_SIZE..=MAX_BOARD_SIZE
+///
+/// Deserializes from a single number (square board) or `{"width": w, "height": h}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "BoardSizeSpec")]
+pub struct BoardSize {
+    width: usize,
+    height: usize,
+}
+
+impl BoardSize {
+    pub fn new(width: usize, height: usize) -> Result<Self, String> {
+        let range = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+        if !range.contains(&width) || !range.contains(&height) {
+            return Err(format!(
+                "Board size {}x{} not supported (each side must be {} to {})",
+                width, height, MIN_BOARD_SIZE, MAX_BOARD_SIZE
+            ));
+        }
+        Ok(Self { width, height })
+    }
+
+    pub fn square(size: usize) -> Result<Self, String> {
+        Self::new(size, size)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.width == self.height
+    }
+
+    /// Number of intersections
+    pub fn area(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        Self { width: 19, height: 19 }
+    }
+}
+
+impl std::fmt::Display for BoardSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+/// Unvalidated board size as sent by clients
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BoardSizeSpec {
+    Square(usize),
+    Rect { width: usize, height: usize },
+}
+
+impl TryFrom<BoardSizeSpec> for BoardSize {
+    type Error = String;
+
+    fn try_from(spec: BoardSizeSpec) -> Result<Self, String> {
+        match spec {
+            BoardSizeSpec::Square(size) => Self::square(size),
+            BoardSizeSpec::Rect { width, height } => Self::new(width, height),
+        }
+    }
+}
+
+/// A connected group of stones on the board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Group {
+    pub id: usize, // Row-major index of the group's first stone
+    pub color: Color,
+    pub stones: Vec<Position>,
+    pub liberties: usize,
+    pub atari: bool, // Can be captured on the next move
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Position {
     pub x: usize,
     pub y: usize,
@@ -27,35 +203,73 @@ impl Position {
         Self { x, y }
     }
 
-    pub fn is_valid(&self) -> bool {
-        self.is_valid_for_size(19)
-    }
-
-    pub fn is_valid_for_size(&self, size: usize) -> bool {
-        self.x < size && self.y < size
+    /// Whether the position lies on a board of the given size
+    pub fn is_valid(&self, size: BoardSize) -> bool {
+        self.x < size.width && self.y < size.height
     }
 
     /// Get adjacent positions (up, down, left, right)
-    pub fn adjacent(&self) -> Vec<Position> {
-        self.adjacent_for_size(19)
-    }
-
-    pub fn adjacent_for_size(&self, size: usize) -> Vec<Position> {
+    pub fn adjacent(&self, size: BoardSize) -> Vec<Position> {
         let mut positions = Vec::new();
 
         if self.x > 0 {
             positions.push(Position::new(self.x - 1, self.y));
         }
-        if self.x < size - 1 {
+        if self.x + 1 < size.width {
             positions.push(Position::new(self.x + 1, self.y));
         }
         if self.y > 0 {
             positions.push(Position::new(self.x, self.y - 1));
         }
-        if self.y < size - 1 {
+        if self.y + 1 < size.height {
             positions.push(Position::new(self.x, self.y + 1));
         }
 
         positions
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_result_format() {
+        assert_eq!(GameResult::from_margin(3.5).to_string(), "B+3.5");
+        assert_eq!(GameResult::from_margin(-7.5).to_string(), "W+7.5");
+        assert_eq!(GameResult::from_margin(0.0).to_string(), "0");
+        assert_eq!(GameResult::Resignation { winner: Color::Black }.to_string(), "B+R");
+        assert_eq!(GameResult::Time { winner: Color::Black }.to_string(), "B+T");
+        assert_eq!(GameResult::Forfeit { winner: Color::White }.to_string(), "W+F");
+    }
+
+    #[test]
+    fn test_board_size_bounds() {
+        assert!(BoardSize::square(1).is_err());
+        assert!(BoardSize::square(26).is_err());
+        assert!(BoardSize::new(25, 2).is_ok());
+        assert!(BoardSize::new(0, 9).is_err());
+    }
+
+    #[test]
+    fn test_board_size_deserialization() {
+        let square: BoardSize = serde_json::from_str("9").unwrap();
+        assert_eq!(square, BoardSize::square(9).unwrap());
+
+        let rect: BoardSize = serde_json::from_str(r#"{"width": 13, "height": 9}"#).unwrap();
+        assert_eq!((rect.width(), rect.height()), (13, 9));
+
+        assert!(serde_json::from_str::<BoardSize>("0").is_err());
+        assert!(serde_json::from_str::<BoardSize>("1000").is_err());
+    }
+
+    #[test]
+    fn test_position_validity_on_rectangular_board() {
+        let size = BoardSize::new(5, 3).unwrap();
+        assert!(Position::new(4, 2).is_valid(size));
+        assert!(!Position::new(2, 4).is_valid(size));
+
+        assert_eq!(Position::new(4, 2).adjacent(size).len(), 2);
+        assert_eq!(Position::new(2, 1).adjacent(size).len(), 4);
+    }
+}
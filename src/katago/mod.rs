@@ -1,11 +1,18 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 
-use crate::game::{Color, Position};
+use crate::game::{BoardSize, Color, Game, HandicapCompensation, KoRule, MoveKind, Position, Ruleset, RulesetName, ScoringMethod};
+
+/// Search depth for hints, deeper than ownership estimates so the ranking is reliable
+const HINT_VISITS: u32 = 200;
 
 /// Configuration for KataGo service
 #[derive(Debug, Clone)]
@@ -13,6 +20,7 @@ pub struct KataGoConfig {
     pub binary_path: PathBuf,
     pub model_path: PathBuf,
     pub config_path: PathBuf,
+    pub human_model_path: PathBuf, // Optional human-SL model for human-like AI play
     pub max_visits: u32,
     pub enable_ownership: bool,
 }
@@ -23,6 +31,7 @@ impl Default for KataGoConfig {
             binary_path: PathBuf::from("assets/katago/katago"),
             model_path: PathBuf::from("assets/katago/model.bin.gz"),
             config_path: PathBuf::from("assets/katago/analysis.cfg"),
+            human_model_path: PathBuf::from("assets/katago/b18c384nbt-humanv0.bin.gz"),
             max_visits: 50, // Optimized for fast territory estimation
             enable_ownership: true,
         }
@@ -37,19 +46,80 @@ pub struct OwnershipData {
     /// Standard deviation/confidence for each intersection
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ownership_stdev: Option<Vec<Vec<f32>>>,
+    /// KataGo's judgement of the whole position, if it reported one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluation: Option<Evaluation>,
+}
+
+/// How the game stands according to KataGo, from Black's point of view
+/// (`reportAnalysisWinratesAs = BLACK` in analysis.cfg)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Evaluation {
+    pub winrate: f32,
+    pub score_lead: f32,
+    pub score_stdev: f32,
+    pub visits: u32,
+}
+
+/// How strongly the AI opponent plays
+///
+/// The human-like levels pick the move a player of that rank would most likely choose,
+/// using the human-SL profiles from `gtp_human5k_example.cfg`. They need the human
+/// model; without it they fall back to a weak plain search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AiStrength {
+    Novice, // Human-like 15k
+    #[default]
+    Casual, // Human-like 5k
+    Strong, // Plain search with few visits
+    Maximum,
+}
+
+impl AiStrength {
+    fn max_visits(self) -> u32 {
+        match self {
+            AiStrength::Novice | AiStrength::Casual => 40,
+            AiStrength::Strong => 100,
+            AiStrength::Maximum => 800,
+        }
+    }
+
+    /// Human-SL profile to imitate, if any
+    fn human_profile(self) -> Option<&'static str> {
+        match self {
+            AiStrength::Novice => Some("preaz_15k"),
+            AiStrength::Casual => Some("preaz_5k"),
+            AiStrength::Strong | AiStrength::Maximum => None,
+        }
+    }
 }
 
+/// Queries waiting for their response, by query id
+type Pending = Arc<Mutex<HashMap<String, oneshot::Sender<AnalysisResponse>>>>;
+
 /// KataGo service that manages the analysis process
+///
+/// Queries are multiplexed over one process: each is written with a unique id, and a
+/// reader task hands every response line to the query waiting on that id, so any
+/// number of queries can be in flight at once.
 pub struct KataGoService {
-    process: Option<Child>,
-    stdin: Option<ChildStdin>,
-    stdout: Option<BufReader<ChildStdout>>,
-    cache: Arc<Mutex<HashMap<u64, OwnershipData>>>,
+    engine: tokio::sync::Mutex<Option<Engine>>, // Spawned on first use
+    pending: Pending,
+    next_query: AtomicU64,
+    cache: Mutex<HashMap<u64, OwnershipData>>,
     config: KataGoConfig,
 }
 
+/// A running analysis process
+struct Engine {
+    _process: Child, // Killed on drop
+    stdin: ChildStdin,
+    reader: JoinHandle<()>,
+}
+
 impl KataGoService {
-    /// Create a new KataGo service and spawn the analysis process
+    /// Create a new KataGo service (the analysis process starts with the first query)
     pub fn new(config: KataGoConfig) -> Result<Self, String> {
         // Verify binary exists
         if !config.binary_path.exists() {
@@ -76,25 +146,32 @@ impl KataGoService {
         }
 
         Ok(Self {
-            process: None,
-            stdin: None,
-            stdout: None,
-            cache: Arc::new(Mutex::new(HashMap::new())),
+            engine: tokio::sync::Mutex::new(None),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            next_query: AtomicU64::new(0),
+            cache: Mutex::new(HashMap::new()),
             config,
         })
     }
 
-    /// Spawn the KataGo analysis process
-    fn spawn_process(&mut self) -> Result<(), String> {
-        let mut child = Command::new(&self.config.binary_path)
+    /// Spawn the KataGo analysis process and its response reader
+    fn spawn_process(&self) -> Result<Engine, String> {
+        let mut command = Command::new(&self.config.binary_path);
+        command
             .arg("analysis")
             .arg("-model")
             .arg(&self.config.model_path)
             .arg("-config")
-            .arg(&self.config.config_path)
+            .arg(&self.config.config_path);
+        if self.config.human_model_path.exists() {
+            command.arg("-human-model").arg(&self.config.human_model_path);
+        }
+
+        let mut child = command
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::null()) // Suppress stderr for now
+            .kill_on_drop(true)
             .spawn()
             .map_err(|e| format!("Failed to spawn KataGo process: {}", e))?;
 
@@ -108,39 +185,56 @@ impl KataGoService {
             .take()
             .ok_or("Failed to open KataGo stdout")?;
 
-        self.process = Some(child);
-        self.stdin = Some(stdin);
-        self.stdout = Some(BufReader::new(stdout));
+        Ok(Engine {
+            _process: child,
+            stdin,
+            reader: tokio::spawn(Self::read_responses(stdout, self.pending.clone())),
+        })
+    }
+
+    /// Route each response line to the query waiting for it
+    async fn read_responses(stdout: impl AsyncRead + Unpin, pending: Pending) {
+        let mut lines = BufReader::new(stdout).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let response: AnalysisResponse = match serde_json::from_str(&line) {
+                Ok(response) => response,
+                Err(e) => {
+                    eprintln!("Unreadable KataGo response: {}", e);
+                    continue;
+                }
+            };
+
+            // Warnings come ahead of the real response to the same query
+            if let Some(warning) = &response.warning {
+                eprintln!("KataGo warning for {}: {}", response.id, warning);
+                continue;
+            }
+
+            if let Some(sender) = pending.lock().unwrap().remove(&response.id) {
+                let _ = sender.send(response);
+            }
+        }
 
-        Ok(())
+        // The process is gone: dropping the senders fails every query still waiting
+        pending.lock().unwrap().clear();
     }
 
     /// Get ownership data for the current board position
-    pub fn get_ownership(&mut self, board: &[Vec<Option<Color>>], board_size: usize) -> Result<OwnershipData, String> {
-        // Check cache
-        let hash = Self::position_hash(board, board_size);
+    pub async fn get_ownership(&self, game: &Game) -> Result<OwnershipData, String> {
+        let mut query = Self::position_query(game, self.config.max_visits);
+        query.include_ownership = self.config.enable_ownership;
+
+        // Check cache (keyed on the whole query: moves, rules, komi and settings)
+        let board_size = game.get_board_size();
+        let hash = Self::query_hash(&query);
         if let Some(cached) = self.cache.lock().unwrap().get(&hash) {
             return Ok(cached.clone());
         }
-
-        // Convert board to initial stones
-        let initial_stones = Self::board_to_initial_stones(board, board_size);
-
-        // Create query
-        let query = AnalysisQuery {
-            id: format!("ownership_{}", hash),
-            moves: Vec::new(),
-            initial_stones,
-            rules: "tromp-taylor".to_string(),
-            komi: 7.5,
-            board_x_size: board_size,
-            board_y_size: board_size,
-            max_visits: self.config.max_visits,
-            include_ownership: true,
-        };
+        query.id = format!("ownership_{}", hash);
 
         // Send query and get response
-        let response = self.send_query(query)?;
+        let response = self.send_query(query).await?;
 
         // Extract ownership data
         let ownership_flat = response.ownership.ok_or("No ownership data in response")?;
@@ -151,6 +245,12 @@ impl KataGoService {
         let ownership_data = OwnershipData {
             ownership,
             ownership_stdev: None,
+            evaluation: response.root_info.map(|root| Evaluation {
+                winrate: root.winrate,
+                score_lead: root.score_lead,
+                score_stdev: root.score_stdev,
+                visits: root.visits,
+            }),
         };
 
         // Cache result
@@ -159,65 +259,182 @@ impl KataGoService {
         Ok(ownership_data)
     }
 
+    /// Choose a move for the player to move, or None to pass
+    pub async fn get_ai_move(&self, game: &Game, strength: AiStrength) -> Result<Option<Position>, String> {
+        let mut query = Self::position_query(game, strength.max_visits());
+        query.id = format!("ai_{}", game.moves().len());
+        if let Some(profile) = strength.human_profile() {
+            // Search settings from gtp_human5k_example.cfg
+            query.override_settings = Some(serde_json::json!({
+                "humanSLProfile": profile,
+                "humanSLCpuctExploration": 0.5,
+                "humanSLCpuctPermanent": 0.2,
+            }));
+        }
+
+        let response = self.send_query(query).await?;
+        let best = Self::choose_move(&response.move_infos, strength).ok_or("No candidate moves in response")?;
 
-    /// Send a query to KataGo and receive the response
-    fn send_query(&mut self, query: AnalysisQuery) -> Result<AnalysisResponse, String> {
-        // Ensure process is running
-        if self.process.is_none() {
-            self.spawn_process()?;
+        if best.move_coord.eq_ignore_ascii_case("pass") {
+            return Ok(None);
         }
+        Self::parse_gtp_move(&best.move_coord, game.get_board_size()).map(Some)
+    }
 
-        let stdin = self.stdin.as_mut().ok_or("KataGo stdin not available")?;
-        let stdout = self.stdout.as_mut().ok_or("KataGo stdout not available")?;
+    /// Get KataGo's best `count` moves for the player to move, best first
+    pub async fn get_hints(&self, game: &Game, count: usize) -> Result<Vec<Hint>, String> {
+        let mut query = Self::position_query(game, HINT_VISITS);
+        query.id = format!("hints_{}", game.moves().len());
+
+        let response = self.send_query(query).await?;
+        Ok(response.move_infos
+            .iter()
+            .filter_map(|info| Self::hint(info, game.get_board_size()))
+            .take(count)
+            .collect())
+    }
 
-        // Serialize and send query
-        let query_json = serde_json::to_string(&query)
-            .map_err(|e| format!("Failed to serialize query: {}", e))?;
+    /// Convert a candidate move, and the variation it leads into, to board positions
+    ///
+    /// A candidate off the board is skipped; a variation is cut at its first unreadable move.
+    fn hint(info: &MoveInfo, board_size: BoardSize) -> Option<Hint> {
+        let point = |gtp: &str| -> Result<Option<Position>, String> {
+            if gtp.eq_ignore_ascii_case("pass") {
+                return Ok(None);
+            }
+            Self::parse_gtp_move(gtp, board_size).map(Some)
+        };
+
+        Some(Hint {
+            position: point(&info.move_coord).ok()?,
+            visits: info.visits,
+            winrate: info.winrate,
+            score_lead: info.score_lead,
+            pv: info.pv.iter().map_while(|gtp| point(gtp).ok()).collect(),
+        })
+    }
 
-        writeln!(stdin, "{}", query_json)
-            .map_err(|e| format!("Failed to write to KataGo: {}", e))?;
+    /// Human-like play follows the human policy, otherwise the most searched move wins
+    fn choose_move(move_infos: &[MoveInfo], strength: AiStrength) -> Option<&MoveInfo> {
+        let has_human_policy = move_infos.iter().any(|info| info.human_prior.is_some());
+        if strength.human_profile().is_some() && has_human_policy {
+            move_infos.iter().max_by(|a, b| a.human_prior.unwrap_or(0.0).total_cmp(&b.human_prior.unwrap_or(0.0)))
+        } else {
+            move_infos.iter().max_by_key(|info| info.visits)
+        }
+    }
 
-        stdin.flush()
-            .map_err(|e| format!("Failed to flush KataGo stdin: {}", e))?;
+    /// Build a query for the game's current position (the id is left for the caller)
+    ///
+    /// Sends the setup stones and the actual moves, so KataGo knows the ko state,
+    /// the captures and whose turn it is.
+    fn position_query(game: &Game, max_visits: u32) -> AnalysisQuery {
+        let board_size = game.get_board_size();
+        let ruleset = game.get_ruleset();
+        let stone = |color: Color, gtp: String| (Self::color_to_gtp(color).to_string(), gtp);
+
+        let initial_stones: Vec<_> = game.get_setup()
+            .iter()
+            .map(|&(pos, color)| stone(color, Self::position_to_gtp(pos, board_size)))
+            .collect();
+
+        let mut moves: Vec<_> = game.moves()
+            .iter()
+            .filter_map(|mv| match mv.kind {
+                MoveKind::Stone(pos) => Some(stone(mv.color, Self::position_to_gtp(pos, board_size))),
+                MoveKind::Pass => Some(stone(mv.color, "pass".to_string())),
+                MoveKind::Resign => None,
+            })
+            .collect();
+
+        // KataGo counts handicap stones itself from an all-black setup and applies the
+        // rules' white bonus; with white setup stones it cannot, so the bonus goes into komi
+        let white_setup = game.get_setup().iter().any(|&(_, color)| color == Color::White);
+        let komi = if white_setup {
+            ruleset.komi + ruleset.handicap_bonus(game.get_handicap())
+        } else {
+            ruleset.komi
+        };
 
-        // Read response
-        let mut response_line = String::new();
-        stdout.read_line(&mut response_line)
-            .map_err(|e| format!("Failed to read from KataGo: {}", e))?;
+        // The player to move once the setup stones are down
+        let initial_player = game.moves().first().map_or(game.get_turn(), |mv| mv.color);
 
-        // Deserialize response
-        let response: AnalysisResponse = serde_json::from_str(&response_line)
-            .map_err(|e| format!("Failed to parse KataGo response: {}", e))?;
+        // Resuming from scoring can hand the move to the player who moved last;
+        // KataGo goes by the move list, so a pass for the other side says so
+        let last_mover = game.moves().iter().rev().find(|mv| mv.kind != MoveKind::Resign).map(|mv| mv.color);
+        if last_mover == Some(game.get_turn()) {
+            moves.push(stone(game.get_turn().opposite(), "pass".to_string()));
+        }
 
-        Ok(response)
+        AnalysisQuery {
+            id: String::new(),
+            moves,
+            initial_stones,
+            initial_player: Self::color_to_gtp(initial_player).to_string(),
+            rules: Self::rules_to_katago(ruleset),
+            komi,
+            board_x_size: board_size.width(),
+            board_y_size: board_size.height(),
+            max_visits,
+            include_ownership: false,
+            override_settings: None,
+        }
     }
 
-    /// Convert board state to initial stones for KataGo
-    fn board_to_initial_stones(board: &[Vec<Option<Color>>], board_size: usize) -> Vec<(String, String)> {
-        let mut stones = Vec::new();
+    /// Send a query to KataGo and wait for its response
+    async fn send_query(&self, mut query: AnalysisQuery) -> Result<AnalysisResponse, String> {
+        // Ids must be unique among queries in flight
+        query.id = format!("{}_{}", query.id, self.next_query.fetch_add(1, Ordering::Relaxed));
 
-        for (y, row) in board.iter().enumerate().take(board_size) {
-            for (x, &cell) in row.iter().enumerate().take(board_size) {
-                if let Some(color) = cell {
-                    let pos = Position::new(x, y);
-                    let gtp_coord = Self::position_to_gtp(pos, board_size);
-                    let color_str = match color {
-                        Color::Black => "B",
-                        Color::White => "W",
-                    };
-                    stones.push((color_str.to_string(), gtp_coord));
-                }
+        let query_json = serde_json::to_string(&query)
+            .map_err(|e| format!("Failed to serialize query: {}", e))?;
+
+        // Only the write is serialized; the response is awaited without holding the engine
+        let (sender, receiver) = oneshot::channel();
+        let written = {
+            let mut engine = self.engine.lock().await;
+
+            // (Re)start the process if it has not been spawned yet or has exited
+            if engine.as_ref().is_none_or(|engine| engine.reader.is_finished()) {
+                *engine = Some(self.spawn_process()?);
+            }
+            let stdin = &mut engine.as_mut().unwrap().stdin;
+
+            self.pending.lock().unwrap().insert(query.id.clone(), sender);
+
+            match stdin.write_all(format!("{}\n", query_json).as_bytes()).await {
+                Ok(()) => stdin.flush().await,
+                Err(e) => Err(e),
             }
+        };
+
+        if let Err(e) = written {
+            self.pending.lock().unwrap().remove(&query.id);
+            return Err(format!("Failed to write to KataGo: {}", e));
+        }
+
+        let response = receiver.await
+            .map_err(|_| "KataGo exited before answering".to_string())?;
+
+        match response.error {
+            Some(error) => Err(format!("KataGo rejected query: {}", error)),
+            None => Ok(response),
         }
+    }
 
-        stones
+    /// Convert color to GTP player ("B" or "W")
+    fn color_to_gtp(color: Color) -> &'static str {
+        match color {
+            Color::Black => "B",
+            Color::White => "W",
+        }
     }
 
     /// Convert position to GTP coordinate (e.g., (3, 3) -> "D4")
-    fn position_to_gtp(pos: Position, board_size: usize) -> String {
-        // GTP uses letters A-T (skipping I) for columns, numbers 1-19 for rows
+    fn position_to_gtp(pos: Position, board_size: BoardSize) -> String {
+        // GTP uses letters A-Z (skipping I) for columns, numbers counted from the bottom for rows
         // x=0 -> A, x=1 -> B, ..., x=7 -> H, x=8 -> J (skip I), ...
-        // y=0 -> bottom row (19 for 19x19), y=18 -> top row (1)
+        // y=0 -> top row (19 for 19x19), y=18 -> bottom row (1)
 
         let col_char = if pos.x < 8 {
             (b'A' + pos.x as u8) as char
@@ -225,13 +442,13 @@ impl KataGoService {
             (b'A' + pos.x as u8 + 1) as char // Skip 'I'
         };
 
-        let row_num = board_size - pos.y;
+        let row_num = board_size.height() - pos.y;
 
         format!("{}{}", col_char, row_num)
     }
 
     /// Parse GTP coordinate to Position (e.g., "D4" -> (3, 3))
-    fn parse_gtp_move(gtp: &str, board_size: usize) -> Result<Position, String> {
+    fn parse_gtp_move(gtp: &str, board_size: BoardSize) -> Result<Position, String> {
         if gtp == "pass" {
             return Err("Pass move".to_string());
         }
@@ -244,56 +461,97 @@ impl KataGoService {
         let row_num: usize = row_str.parse().map_err(|_| "Invalid row number")?;
 
         // Convert column letter to x coordinate
-        let x = if col_char < 'I' {
-            (col_char as u8 - b'A') as usize
-        } else {
-            (col_char as u8 - b'A' - 1) as usize // Account for skipped 'I'
+        let x = match col_char {
+            'A'..='H' => (col_char as u8 - b'A') as usize,
+            'J'..='Z' => (col_char as u8 - b'A' - 1) as usize, // Account for skipped 'I'
+            _ => return Err("Invalid column letter".to_string()),
         };
 
         // Convert row number to y coordinate
-        let y = board_size - row_num;
+        if row_num == 0 || row_num > board_size.height() {
+            return Err("Row off the board".to_string());
+        }
+        let pos = Position::new(x, board_size.height() - row_num);
 
-        Ok(Position::new(x, y))
+        if !pos.is_valid(board_size) {
+            return Err("Column off the board".to_string());
+        }
+        Ok(pos)
     }
 
-    /// Convert flattened ownership array to 2D
-    fn unflatten_ownership(flat: &[f32], board_size: usize) -> Vec<Vec<f32>> {
-        flat.chunks(board_size)
-            .map(|chunk| chunk.to_vec())
-            .collect()
+    /// Convert a ruleset to KataGo's `rules` field: a preset name, or a rules object for custom rules
+    fn rules_to_katago(ruleset: &Ruleset) -> serde_json::Value {
+        if ruleset.is_preset() {
+            let name = match ruleset.name {
+                RulesetName::Japanese => Some("japanese"),
+                RulesetName::Aga => Some("aga"),
+                RulesetName::Korean => Some("korean"),
+                RulesetName::NewZealand => Some("new-zealand"),
+                RulesetName::TrompTaylor => Some("tromp-taylor"),
+                // KataGo's "chinese" uses simple ko; ours is positional superko, so spell it out
+                RulesetName::Chinese => None,
+                RulesetName::Custom => unreachable!("custom rules are never a preset"),
+            };
+            if let Some(name) = name {
+                return serde_json::Value::from(name);
+            }
+        }
+
+        serde_json::json!({
+            "ko": match ruleset.ko {
+                KoRule::Simple => "SIMPLE",
+                KoRule::PositionalSuperko => "POSITIONAL",
+                KoRule::SituationalSuperko => "SITUATIONAL",
+            },
+            "scoring": match ruleset.scoring {
+                ScoringMethod::Area => "AREA",
+                ScoringMethod::Territory => "TERRITORY",
+            },
+            "tax": "NONE",
+            "suicide": ruleset.suicide_allowed,
+            "hasButton": false,
+            "whiteHandicapBonus": match ruleset.handicap_compensation {
+                HandicapCompensation::None => "0",
+                HandicapCompensation::N => "N",
+                HandicapCompensation::NMinusOne => "N-1",
+            },
+        })
     }
 
-    /// Hash the board position for caching
-    fn position_hash(board: &[Vec<Option<Color>>], board_size: usize) -> u64 {
+    /// Hash everything a query asks about, for caching (the id is left out)
+    fn query_hash(query: &AnalysisQuery) -> u64 {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
         let mut hasher = DefaultHasher::new();
-        board_size.hash(&mut hasher);
-
-        for row in board.iter().take(board_size) {
-            for cell in row.iter().take(board_size) {
-                match cell {
-                    None => 0u8.hash(&mut hasher),
-                    Some(Color::Black) => 1u8.hash(&mut hasher),
-                    Some(Color::White) => 2u8.hash(&mut hasher),
-                }
-            }
-        }
+        serde_json::to_value(query)
+            .map(|mut value| {
+                value["id"] = serde_json::Value::Null;
+                value.to_string()
+            })
+            .unwrap_or_default()
+            .hash(&mut hasher);
         hasher.finish()
     }
-}
 
-impl Drop for KataGoService {
-    fn drop(&mut self) {
-        // Clean up: kill the KataGo process
-        if let Some(mut process) = self.process.take() {
-            let _ = process.kill();
-            let _ = process.wait();
-        }
+    /// Convert flattened ownership array to 2D
+    fn unflatten_ownership(flat: &[f32], board_size: BoardSize) -> Vec<Vec<f32>> {
+        flat.chunks(board_size.width())
+            .map(|chunk| chunk.to_vec())
+            .collect()
     }
 }
 
+/// A move KataGo suggests, with the same point of view as [`Evaluation`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hint {
+    pub position: Option<Position>, // None for a pass
+    pub visits: u32,
+    pub winrate: f32,
+    pub score_lead: f32,
+    pub pv: Vec<Option<Position>>, // Expected continuation, starting with this move
+}
+
 /// JSON query sent to KataGo analysis engine
 #[derive(Debug, Serialize, Deserialize)]
 struct AnalysisQuery {
@@ -301,7 +559,10 @@ struct AnalysisQuery {
     moves: Vec<(String, String)>, // e.g. [["B", "Q4"], ["W", "D16"]] - must always be present
     #[serde(rename = "initialStones", skip_serializing_if = "Vec::is_empty")]
     initial_stones: Vec<(String, String)>, // e.g. [["B", "Q4"], ["W", "D16"]]
-    rules: String,
+    #[serde(rename = "initialPlayer")]
+    initial_player: String, // Player to move after the initial stones
+
+    rules: serde_json::Value, // Preset name or full rules object
     komi: f32,
     #[serde(rename = "boardXSize")]
     board_x_size: usize,
@@ -311,6 +572,8 @@ struct AnalysisQuery {
     max_visits: u32,
     #[serde(rename = "includeOwnership")]
     include_ownership: bool,
+    #[serde(rename = "overrideSettings", skip_serializing_if = "Option::is_none")]
+    override_settings: Option<serde_json::Value>, // Per-query config, e.g. a human-SL profile
 }
 
 /// JSON response from KataGo analysis engine
@@ -319,6 +582,41 @@ struct AnalysisResponse {
     id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     ownership: Option<Vec<f32>>, // Flattened array
+    #[serde(rename = "moveInfos", default)]
+    move_infos: Vec<MoveInfo>,
+    #[serde(rename = "rootInfo", skip_serializing_if = "Option::is_none")]
+    root_info: Option<RootInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>, // The query was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>, // A note about the query; the response follows separately
+}
+
+/// Summary of the search at the root position
+#[derive(Debug, Serialize, Deserialize)]
+struct RootInfo {
+    winrate: f32,
+    #[serde(rename = "scoreLead")]
+    score_lead: f32,
+    #[serde(rename = "scoreStdev")]
+    score_stdev: f32,
+    visits: u32,
+}
+
+/// One candidate move from KataGo's search
+#[derive(Debug, Serialize, Deserialize)]
+struct MoveInfo {
+    #[serde(rename = "move")]
+    move_coord: String, // GTP coordinate or "pass"
+    visits: u32,
+    #[serde(rename = "humanPrior", skip_serializing_if = "Option::is_none")]
+    human_prior: Option<f32>, // Only with a human-SL profile and model
+    #[serde(default)]
+    winrate: f32,
+    #[serde(rename = "scoreLead", default)]
+    score_lead: f32,
+    #[serde(default)]
+    pv: Vec<String>, // Principal variation in GTP coordinates
 }
 
 
@@ -327,46 +625,205 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_position_hash() {
-        let empty_board: Vec<Vec<Option<Color>>> = vec![vec![None; 19]; 19];
-        let hash1 = KataGoService::position_hash(&empty_board, 19);
-        let hash2 = KataGoService::position_hash(&empty_board, 19);
-        assert_eq!(hash1, hash2, "Same position should have same hash");
+    fn test_query_hash() {
+        let query = |game: &Game| KataGoService::position_query(game, 50);
+        let empty = Game::new();
+        assert_eq!(KataGoService::query_hash(&query(&empty)), KataGoService::query_hash(&query(&empty)));
+
+        // Ids do not matter, the position does
+        let mut renamed = query(&empty);
+        renamed.id = "other".to_string();
+        assert_eq!(KataGoService::query_hash(&renamed), KataGoService::query_hash(&query(&empty)));
+
+        let mut game = Game::new();
+        game.place_stone(Position::new(0, 0), Color::Black).unwrap();
+        assert_ne!(KataGoService::query_hash(&query(&game)), KataGoService::query_hash(&query(&empty)));
+    }
+
+    #[test]
+    fn test_position_query_sends_moves_and_setup() {
+        let size = BoardSize::square(9).unwrap();
+        let mut game = Game::with_size(size, Ruleset::preset(RulesetName::Aga));
+        game.setup_handicap(2, crate::game::HandicapPlacement::Fixed).unwrap();
+        game.place_stone(Position::new(4, 4), Color::White).unwrap();
+        game.pass(Color::Black).unwrap();
+
+        let query = KataGoService::position_query(&game, 50);
+        assert_eq!(query.initial_stones.len(), 2);
+        assert!(query.initial_stones.iter().all(|(color, _)| color == "B"));
+        assert_eq!(query.moves, vec![
+            ("W".to_string(), "E5".to_string()),
+            ("B".to_string(), "pass".to_string()),
+        ]);
+        assert_eq!(query.initial_player, "W");
+        assert_eq!(query.rules, serde_json::json!("aga"));
+        assert_eq!(query.komi, game.get_ruleset().komi); // KataGo applies the handicap bonus itself
+        assert_eq!((query.board_x_size, query.board_y_size), (9, 9));
+    }
 
-        let mut board_with_stone: Vec<Vec<Option<Color>>> = vec![vec![None; 19]; 19];
-        board_with_stone[0][0] = Some(Color::Black);
-        let hash3 = KataGoService::position_hash(&board_with_stone, 19);
-        assert_ne!(hash1, hash3, "Different positions should have different hashes");
+    #[test]
+    fn test_position_query_after_resume() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        game.place_stone(Position::new(4, 4), Color::Black).unwrap();
+        game.pass(Color::White).unwrap();
+        game.pass(Color::Black).unwrap();
+
+        // Black resumes: White to move, as the move list already implies
+        let mut black_resumes = game.clone();
+        black_resumes.resume(Color::Black).unwrap();
+        assert_eq!(KataGoService::position_query(&black_resumes, 50).moves.len(), 3);
+
+        // White resumes: Black to move again, which takes an extra pass for White
+        game.resume(Color::White).unwrap();
+        let query = KataGoService::position_query(&game, 50);
+        assert_eq!(query.moves.len(), 4);
+        assert_eq!(query.moves.last().unwrap(), &("W".to_string(), "pass".to_string()));
     }
 
     #[test]
     fn test_config_default() {
         let config = KataGoConfig::default();
-        assert_eq!(config.max_visits, 100);
+        // Ownership runs after every move, so the search stays short (lowered from 100, see CHANGELOG)
+        assert_eq!(config.max_visits, 50);
         assert!(config.enable_ownership);
     }
 
     #[test]
     fn test_gtp_conversion() {
         // Test position to GTP
-        assert_eq!(KataGoService::position_to_gtp(Position::new(0, 0), 19), "A19");
-        assert_eq!(KataGoService::position_to_gtp(Position::new(3, 3), 19), "D16");
-        assert_eq!(KataGoService::position_to_gtp(Position::new(8, 8), 19), "J11"); // Skip I
-        assert_eq!(KataGoService::position_to_gtp(Position::new(18, 18), 19), "T1");
+        assert_eq!(KataGoService::position_to_gtp(Position::new(0, 0), BoardSize::default()), "A19");
+        assert_eq!(KataGoService::position_to_gtp(Position::new(3, 3), BoardSize::default()), "D16");
+        assert_eq!(KataGoService::position_to_gtp(Position::new(8, 8), BoardSize::default()), "J11"); // Skip I
+        assert_eq!(KataGoService::position_to_gtp(Position::new(18, 18), BoardSize::default()), "T1");
 
         // Test GTP to position
-        assert_eq!(KataGoService::parse_gtp_move("A19", 19).unwrap(), Position::new(0, 0));
-        assert_eq!(KataGoService::parse_gtp_move("D16", 19).unwrap(), Position::new(3, 3));
-        assert_eq!(KataGoService::parse_gtp_move("Q4", 19).unwrap(), Position::new(15, 15));
+        assert_eq!(KataGoService::parse_gtp_move("A19", BoardSize::default()).unwrap(), Position::new(0, 0));
+        assert_eq!(KataGoService::parse_gtp_move("D16", BoardSize::default()).unwrap(), Position::new(3, 3));
+        assert_eq!(KataGoService::parse_gtp_move("Q4", BoardSize::default()).unwrap(), Position::new(15, 15));
 
         // Test pass
-        assert!(KataGoService::parse_gtp_move("pass", 19).is_err());
+        assert!(KataGoService::parse_gtp_move("pass", BoardSize::default()).is_err());
+    }
+
+    #[test]
+    fn test_gtp_conversion_rectangular() {
+        // Rows count from the bottom of the board's own height
+        let size = BoardSize::new(25, 7).unwrap();
+        assert_eq!(KataGoService::position_to_gtp(Position::new(24, 0), size), "Z7");
+        assert_eq!(KataGoService::position_to_gtp(Position::new(0, 6), size), "A1");
+        assert_eq!(KataGoService::parse_gtp_move("Z7", size).unwrap(), Position::new(24, 0));
+
+        assert!(KataGoService::parse_gtp_move("A8", size).is_err());
+        assert!(KataGoService::parse_gtp_move("K1", BoardSize::square(9).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_unflatten_rectangular_ownership() {
+        let flat: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        let unflat = KataGoService::unflatten_ownership(&flat, BoardSize::new(4, 3).unwrap());
+
+        assert_eq!(unflat.len(), 3);
+        assert_eq!(unflat[1], vec![4.0, 5.0, 6.0, 7.0]);
+    }
+
+    #[test]
+    fn test_rules_to_katago() {
+        let japanese = Ruleset::preset(RulesetName::Japanese);
+        assert_eq!(KataGoService::rules_to_katago(&japanese), serde_json::json!("japanese"));
+
+        let chinese = KataGoService::rules_to_katago(&Ruleset::preset(RulesetName::Chinese));
+        assert_eq!(chinese["ko"], "POSITIONAL");
+        assert_eq!(chinese["scoring"], "AREA");
+        assert_eq!(chinese["whiteHandicapBonus"], "N");
+
+        let mut custom = Ruleset::preset(RulesetName::NewZealand);
+        custom.ko = KoRule::Simple;
+        let rules = KataGoService::rules_to_katago(&custom);
+        assert_eq!(rules["ko"], "SIMPLE");
+        assert_eq!(rules["scoring"], "AREA");
+        assert_eq!(rules["suicide"], true);
+        assert_eq!(rules["whiteHandicapBonus"], "0");
+    }
+
+    #[test]
+    fn test_ai_strength_settings() {
+        assert_eq!(AiStrength::default(), AiStrength::Casual);
+        assert_eq!(AiStrength::Casual.human_profile(), Some("preaz_5k"));
+        assert_eq!(AiStrength::Maximum.human_profile(), None);
+        assert!(AiStrength::Strong.max_visits() < AiStrength::Maximum.max_visits());
+        assert_eq!(serde_json::from_str::<AiStrength>("\"novice\"").unwrap(), AiStrength::Novice);
+    }
+
+    #[test]
+    fn test_choose_ai_move() {
+        let json = r#"{"id":"ai_0","moveInfos":[
+            {"move":"D4","visits":30,"humanPrior":0.1,"winrate":0.5},
+            {"move":"Q16","visits":10,"humanPrior":0.4,"winrate":0.4}
+        ]}"#;
+        let response: AnalysisResponse = serde_json::from_str(json).unwrap();
+
+        let human = KataGoService::choose_move(&response.move_infos, AiStrength::Casual).unwrap();
+        assert_eq!(human.move_coord, "Q16");
+        let search = KataGoService::choose_move(&response.move_infos, AiStrength::Strong).unwrap();
+        assert_eq!(search.move_coord, "D4");
+
+        // Without the human model there is no human policy to follow
+        let plain: AnalysisResponse = serde_json::from_str(r#"{"id":"x","moveInfos":[{"move":"pass","visits":5}]}"#).unwrap();
+        assert_eq!(KataGoService::choose_move(&plain.move_infos, AiStrength::Novice).unwrap().move_coord, "pass");
+    }
+
+    #[test]
+    fn test_hint_from_move_info() {
+        let json = r#"{"id":"hints_0","moveInfos":[
+            {"move":"E5","visits":120,"winrate":0.55,"scoreLead":2.5,"pv":["E5","C3","pass"]},
+            {"move":"Z9","visits":1,"pv":["Z9"]},
+            {"move":"D4","visits":1,"pv":["D4","Z9","E5"]}
+        ]}"#;
+        let response: AnalysisResponse = serde_json::from_str(json).unwrap();
+        let board_size = BoardSize::square(9).unwrap();
+
+        let hint = KataGoService::hint(&response.move_infos[0], board_size).unwrap();
+        assert_eq!(hint.position, Some(Position::new(4, 4)));
+        assert_eq!((hint.visits, hint.winrate, hint.score_lead), (120, 0.55, 2.5));
+        assert_eq!(hint.pv, vec![Some(Position::new(4, 4)), Some(Position::new(2, 6)), None]);
+
+        // An unreadable candidate is dropped; an unreadable variation is cut short
+        assert!(KataGoService::hint(&response.move_infos[1], board_size).is_none());
+        let cut = KataGoService::hint(&response.move_infos[2], board_size).unwrap();
+        assert_eq!(cut.pv, vec![Some(Position::new(3, 5))]);
+    }
+
+    #[tokio::test]
+    async fn test_responses_routed_by_id() {
+        let pending: Pending = Arc::new(Mutex::new(HashMap::new()));
+        let mut receivers = Vec::new();
+        for id in ["a_0", "b_1", "c_2"] {
+            let (sender, receiver) = oneshot::channel();
+            pending.lock().unwrap().insert(id.to_string(), sender);
+            receivers.push(receiver);
+        }
+
+        // Answers arrive out of order, with a warning ahead of one of them
+        let output = concat!(
+            r#"{"id":"b_1","moveInfos":[{"move":"D4","visits":3}]}"#, "\n",
+            r#"{"id":"a_0","warning":"unused field","field":"foo"}"#, "\n",
+            r#"{"id":"a_0","ownership":[0.5]}"#, "\n",
+        );
+        KataGoService::read_responses(output.as_bytes(), pending.clone()).await;
+
+        let mut receivers = receivers.into_iter();
+        assert_eq!(receivers.next().unwrap().await.unwrap().ownership, Some(vec![0.5]));
+        assert_eq!(receivers.next().unwrap().await.unwrap().move_infos[0].move_coord, "D4");
+
+        // Still waiting when the output ended: the query fails instead of hanging
+        assert!(receivers.next().unwrap().await.is_err());
+        assert!(pending.lock().unwrap().is_empty());
     }
 
     #[test]
     fn test_unflatten_ownership() {
         let flat: Vec<f32> = (0..81).map(|i| i as f32).collect();
-        let unflat = KataGoService::unflatten_ownership(&flat, 9);
+        let unflat = KataGoService::unflatten_ownership(&flat, BoardSize::square(9).unwrap());
 
         assert_eq!(unflat.len(), 9);
         assert_eq!(unflat[0].len(), 9);
@@ -376,16 +833,23 @@ mod tests {
     }
 
     #[test]
+    fn test_parse_root_info() {
+        let json = r#"{"id":"ownership_1","ownership":[0.1],
+            "rootInfo":{"winrate":0.42,"scoreLead":-1.5,"scoreStdev":12.0,"visits":51,"utility":-0.1}}"#;
+        let response: AnalysisResponse = serde_json::from_str(json).unwrap();
+
+        let root = response.root_info.unwrap();
+        assert_eq!((root.winrate, root.score_lead, root.score_stdev, root.visits), (0.42, -1.5, 12.0, 51));
+    }
+
+    #[tokio::test]
     #[ignore] // Run with --ignored to test actual KataGo communication
-    fn test_katago_ownership_query() {
+    async fn test_katago_ownership_query() {
         let config = KataGoConfig::default();
-        let mut service = KataGoService::new(config).expect("Failed to create service");
-
-        // Create empty 19x19 board
-        let empty_board: Vec<Vec<Option<Color>>> = vec![vec![None; 19]; 19];
+        let service = KataGoService::new(config).expect("Failed to create service");
 
-        // Query ownership
-        let result = service.get_ownership(&empty_board, 19);
+        // Query ownership for an empty 19x19 board
+        let result = service.get_ownership(&Game::new()).await;
 
         assert!(result.is_ok(), "Failed to get ownership: {:?}", result.err());
         let ownership = result.unwrap();
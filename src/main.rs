@@ -3,10 +3,12 @@ use axum::{
     routing::get,
 };
 use tower_http::services::ServeDir;
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, path::Path, sync::Arc};
 
+mod clock;
 mod game;
 mod katago;
+mod sgf;
 mod state;
 mod ws;
 
@@ -17,8 +19,23 @@ async fn main() {
     // Initialize shared state
     let state = Arc::new(AppState::new());
 
+    // Optionally continue a saved game: `go-server path/to/game.sgf`
+    if let Some(path) = std::env::args().nth(1) {
+        match sgf::load_file(Path::new(&path)) {
+            Ok(game) => {
+                *state.game.lock().await = game;
+                println!("✓ Loaded game from {}", path);
+            }
+            Err(e) => println!("⚠ Could not load {}: {}", path, e),
+        }
+    }
+
+    // Keep game clocks ticking
+    tokio::spawn(clock::run(state.clone()));
+
     let app = Router::new()
         .route("/ws", get(ws::handler))
+        .route("/sgf", get(sgf::download).post(sgf::upload))
         .nest_service("/themes", ServeDir::new("themes"))
         .nest_service("/", ServeDir::new("frontend/dist"))
         .with_state(state);
@@ -26,6 +43,7 @@ async fn main() {
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
     println!("Server running on http://localhost:3000");
     println!("WebSocket endpoint: ws://localhost:3000/ws");
+    println!("SGF download: http://localhost:3000/sgf");
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     axum::serve(listener, app).await.unwrap();
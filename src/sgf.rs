@@ -0,0 +1,687 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::game::{BoardSize, Color, Game, GameResult, MoveKind, Position, Ruleset, RulesetName};
+use crate::state::AppState;
+use crate::ws;
+
+/// A parsed SGF node: property identifiers with their raw values
+type Node = Vec<(String, Vec<String>)>;
+
+/// Serialize a game as an FF[4] SGF record
+pub fn write(game: &Game) -> String {
+    let mut sgf = String::from("(;FF[4]GM[1]CA[UTF-8]");
+    sgf.push_str(&format!("AP[go-server:{}]", env!("CARGO_PKG_VERSION")));
+    let size = game.get_board_size();
+    if size.is_square() {
+        sgf.push_str(&format!("SZ[{}]", size.width()));
+    } else {
+        sgf.push_str(&format!("SZ[{}:{}]", size.width(), size.height()));
+    }
+    sgf.push_str(&format!("KM[{}]", game.get_komi()));
+
+    if let Some(name) = ruleset_to_sgf(game.get_ruleset()) {
+        sgf.push_str(&format!("RU[{}]", name));
+    }
+
+    for (color, tag) in [(Color::Black, "PB"), (Color::White, "PW")] {
+        if let Some(name) = game.get_player_name(color) {
+            sgf.push_str(&format!("{}[{}]", tag, escape(name)));
+        }
+    }
+
+    if game.get_handicap() > 0 {
+        sgf.push_str(&format!("HA[{}]", game.get_handicap()));
+    }
+
+    if let Some(result) = game.get_result() {
+        sgf.push_str(&format!("RE[{}]", result));
+    }
+
+    for color in [Color::Black, Color::White] {
+        let points: String = game.get_setup().iter()
+            .filter(|&&(_, c)| c == color)
+            .map(|&(pos, _)| format!("[{}]", point(pos)))
+            .collect();
+        if !points.is_empty() {
+            sgf.push_str(&format!("A{}{}", color_tag(color), points));
+        }
+    }
+
+    // PL is only needed when White starts (handicap or setup positions)
+    let first_player = game.moves().first().map_or(game.get_turn(), |mv| mv.color);
+    if first_player == Color::White {
+        sgf.push_str("PL[W]");
+    }
+
+    for mv in game.moves() {
+        let tag = color_tag(mv.color);
+        match mv.kind {
+            MoveKind::Stone(pos) => sgf.push_str(&format!("\n;{}[{}]", tag, point(pos))),
+            MoveKind::Pass => sgf.push_str(&format!("\n;{}[]", tag)),
+            MoveKind::Resign => {} // Not a node; reflected in the result
+        }
+    }
+
+    // The agreed count goes on the last node; dead stones lie inside the territory
+    if game.get_final_score().is_some() {
+        let (black, white) = game.territory();
+        for (tag, points) in [("TB", black), ("TW", white)] {
+            if !points.is_empty() {
+                let points: String = points.iter().map(|&pos| format!("[{}]", point(pos))).collect();
+                sgf.push_str(&format!("{}{}", tag, points));
+            }
+        }
+    }
+
+    sgf.push_str(")\n");
+    sgf
+}
+
+/// Load a game from an SGF record, following the main line
+///
+/// Moves are replayed through `Game::place_stone`, so illegal records are rejected.
+pub fn parse(text: &str) -> Result<Game, String> {
+    let nodes = Parser::new(text).main_line()?;
+    let root = nodes.first().ok_or("SGF contains no nodes")?;
+
+    let size = match prop(root, "SZ") {
+        Some(value) => parse_size(value)?,
+        None => BoardSize::default(),
+    };
+
+    let ruleset = prop(root, "RU").and_then(ruleset_from_sgf).map(Ruleset::preset).unwrap_or_default();
+    let mut game = Game::with_size(size, ruleset);
+
+    if let Some(value) = prop(root, "KM") {
+        let komi = value.trim().parse::<f32>().map_err(|_| format!("Invalid komi KM[{}]", value))?;
+        game.set_komi(komi);
+    }
+    if let Some(value) = prop(root, "HA") {
+        let handicap = value.trim().parse::<u8>().map_err(|_| format!("Invalid handicap HA[{}]", value))?;
+        game.set_handicap(handicap);
+    }
+    if let Some(name) = prop(root, "PB").filter(|n| !n.is_empty()) {
+        game.set_player_name(Color::Black, name.to_string());
+    }
+    if let Some(name) = prop(root, "PW").filter(|n| !n.is_empty()) {
+        game.set_player_name(Color::White, name.to_string());
+    }
+
+    // White moves first after handicap stones unless PL says otherwise
+    if game.get_handicap() >= 2 {
+        game.set_first_player(Color::White)?;
+    }
+
+    for (index, node) in nodes.iter().enumerate() {
+        apply_node(&mut game, node, size)
+            .map_err(|e| format!("Node {}: {}", index, e))?;
+    }
+
+    // A recorded result means the game is over; a counted one restores the dead stones
+    if let Some(result) = prop(root, "RE").and_then(parse_result) {
+        if matches!(result, GameResult::Score { .. } | GameResult::Jigo) {
+            let last = nodes.last().unwrap_or(root);
+            let dead = dead_stones(&game, last, size)?;
+            if !dead.is_empty() || prop(last, "TB").is_some() || prop(last, "TW").is_some() {
+                game.restore_score(dead);
+            }
+        }
+        game.end(result);
+    }
+
+    game.take_events(); // Replaying the record is not news
+    Ok(game)
+}
+
+/// Stones standing in the opponent's territory (`TB`/`TW`) of a node
+fn dead_stones(game: &Game, node: &Node, size: BoardSize) -> Result<HashSet<Position>, String> {
+    let board = game.get_board();
+    let mut dead = HashSet::new();
+    for (ident, owner) in [("TB", Color::Black), ("TW", Color::White)] {
+        for value in values(node, ident) {
+            for pos in parse_points(value, size)? {
+                if board[pos.y][pos.x] == Some(owner.opposite()) {
+                    dead.insert(pos);
+                }
+            }
+        }
+    }
+    Ok(dead)
+}
+
+/// Load a game from an SGF file on disk
+pub fn load_file(path: &Path) -> Result<Game, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    parse(&text)
+}
+
+/// Parse a result ("B+R", "W+3.5", "B+T", "W+F", "0" or "Draw"); unknown or void results are ignored
+fn parse_result(value: &str) -> Option<GameResult> {
+    let value = value.trim();
+    if value == "0" || value.eq_ignore_ascii_case("draw") {
+        return Some(GameResult::Jigo);
+    }
+
+    let (winner, how) = value.split_once('+')?;
+    let winner = match winner {
+        "B" | "b" => Color::Black,
+        "W" | "w" => Color::White,
+        _ => return None,
+    };
+
+    match how.to_ascii_uppercase().as_str() {
+        "R" | "RESIGN" => Some(GameResult::Resignation { winner }),
+        "T" | "TIME" => Some(GameResult::Time { winner }),
+        "F" | "FORFEIT" => Some(GameResult::Forfeit { winner }),
+        margin => margin.parse::<f32>().ok()
+            .filter(|&margin| margin > 0.0)
+            .map(|margin| GameResult::Score { winner, margin }),
+    }
+}
+
+/// Parse a board size: `SZ[19]` for square boards, `SZ[19:13]` (columns:rows) otherwise
+fn parse_size(value: &str) -> Result<BoardSize, String> {
+    let dimension = |v: &str| v.trim().parse::<usize>()
+        .map_err(|_| format!("Unsupported board size SZ[{}]", value));
+
+    let size = match value.split_once(':') {
+        Some((width, height)) => BoardSize::new(dimension(width)?, dimension(height)?),
+        None => BoardSize::square(dimension(value)?),
+    };
+    size.map_err(|_| format!("Unsupported board size SZ[{}]", value))
+}
+
+/// Apply setup, turn and move properties of a single node
+fn apply_node(game: &mut Game, node: &Node, size: BoardSize) -> Result<(), String> {
+    for (ident, color) in [("AE", None), ("AB", Some(Color::Black)), ("AW", Some(Color::White))] {
+        for value in values(node, ident) {
+            for pos in parse_points(value, size)? {
+                game.setup_stone(pos, color)?;
+            }
+        }
+    }
+
+    if let Some(value) = prop(node, "PL") {
+        let color = match value.trim() {
+            "B" | "b" => Color::Black,
+            "W" | "w" => Color::White,
+            other => return Err(format!("Invalid player PL[{}]", other)),
+        };
+        game.set_first_player(color)?;
+    }
+
+    for (ident, color) in [("B", Color::Black), ("W", Color::White)] {
+        let Some(value) = prop(node, ident) else {
+            continue;
+        };
+
+        let result = match parse_move(value, size)? {
+            Some(pos) => game.place_stone(pos, color).map(|_| ()),
+            None => game.pass(color),
+        };
+
+        result.map_err(|e| format!("{}[{}]: {}", ident, value, e))?;
+    }
+
+    Ok(())
+}
+
+/// Get the first value of a property
+fn prop<'a>(node: &'a Node, ident: &'a str) -> Option<&'a str> {
+    values(node, ident).next()
+}
+
+/// Get every value of a property
+fn values<'a>(node: &'a Node, ident: &'a str) -> impl Iterator<Item = &'a str> {
+    node.iter()
+        .filter(move |(id, _)| id == ident)
+        .flat_map(|(_, vals)| vals.iter().map(String::as_str))
+}
+
+/// Parse a move value; empty (or `tt` on boards up to 19x19) is a pass
+fn parse_move(value: &str, size: BoardSize) -> Result<Option<Position>, String> {
+    let value = value.trim();
+    if value.is_empty() || (value == "tt" && size.width() <= 19 && size.height() <= 19) {
+        return Ok(None);
+    }
+    parse_point(value, size).map(Some)
+}
+
+/// Parse a point value (e.g. "dp" -> (3, 15))
+fn parse_point(value: &str, size: BoardSize) -> Result<Position, String> {
+    let bytes = value.trim().as_bytes();
+    let coord = |b: u8| match b {
+        b'a'..=b'z' => Some((b - b'a') as usize),
+        _ => None,
+    };
+
+    match bytes {
+        [x, y] => match (coord(*x), coord(*y)) {
+            (Some(x), Some(y)) if Position::new(x, y).is_valid(size) => Ok(Position::new(x, y)),
+            _ => Err(format!("Point [{}] is off the board", value)),
+        },
+        _ => Err(format!("Invalid point [{}]", value)),
+    }
+}
+
+/// Parse a point or a compressed rectangle of points (e.g. "aa:cc")
+fn parse_points(value: &str, size: BoardSize) -> Result<Vec<Position>, String> {
+    let Some((from, to)) = value.split_once(':') else {
+        return Ok(vec![parse_point(value, size)?]);
+    };
+
+    let (a, b) = (parse_point(from, size)?, parse_point(to, size)?);
+    let mut points = Vec::new();
+    for y in a.y.min(b.y)..=a.y.max(b.y) {
+        for x in a.x.min(b.x)..=a.x.max(b.x) {
+            points.push(Position::new(x, y));
+        }
+    }
+    Ok(points)
+}
+
+/// Minimal SGF reader that extracts the main line of the first game tree
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { chars: text.chars().peekable() }
+    }
+
+    fn main_line(&mut self) -> Result<Vec<Node>, String> {
+        self.skip_whitespace();
+        if self.chars.next() != Some('(') {
+            return Err("SGF must start with '('".to_string());
+        }
+        self.game_tree()
+    }
+
+    /// Parse a game tree after its opening '(', keeping only the first variation
+    fn game_tree(&mut self) -> Result<Vec<Node>, String> {
+        let mut nodes = Vec::new();
+        let mut in_variation = false;
+
+        loop {
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(';') if !in_variation => nodes.push(self.node()?),
+                Some('(') if !in_variation => {
+                    nodes.extend(self.game_tree()?);
+                    in_variation = true;
+                }
+                Some('(') => {
+                    self.game_tree()?; // Side variation, discarded
+                }
+                Some(')') => return Ok(nodes),
+                Some(c) => return Err(format!("Unexpected '{}' in SGF", c)),
+                None => return Err("Unexpected end of SGF".to_string()),
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<Node, String> {
+        let mut node = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.chars.peek() {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    // FF[3] allows lowercase letters in identifiers; only uppercase ones count
+                    let mut ident = String::new();
+                    while let Some(&c) = self.chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                        if c.is_ascii_uppercase() {
+                            ident.push(c);
+                        }
+                        self.chars.next();
+                    }
+
+                    let mut vals = Vec::new();
+                    self.skip_whitespace();
+                    while self.chars.peek() == Some(&'[') {
+                        self.chars.next();
+                        vals.push(self.value()?);
+                        self.skip_whitespace();
+                    }
+
+                    if vals.is_empty() {
+                        return Err(format!("Property {} has no value", ident));
+                    }
+                    node.push((ident, vals));
+                }
+                _ => return Ok(node),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<String, String> {
+        let mut value = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('\\') => match self.chars.next() {
+                    Some('\n') => {} // Soft line break
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                Some(']') => return Ok(value),
+                Some(c) => value.push(c),
+                None => break,
+            }
+        }
+
+        Err("Unterminated property value".to_string())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+}
+
+/// HTTP handler: download the current game as an SGF file
+pub async fn download(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let sgf = write(&*state.game.lock().await);
+
+    (
+        [
+            (header::CONTENT_TYPE, "application/x-go-sgf"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"game.sgf\""),
+        ],
+        sgf,
+    )
+}
+
+/// HTTP handler: replace the current game with an uploaded SGF record
+pub async fn upload(State(state): State<Arc<AppState>>, body: String) -> impl IntoResponse {
+    let game = match parse(&body) {
+        Ok(game) => game,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("Invalid SGF: {}", e)),
+    };
+
+    *state.game.lock().await = game;
+    *state.clock.lock().await = None; // Loaded games continue untimed
+    state.evaluations.lock().await.clear();
+
+    // Players (the AI seat included) keep their colors; anyone browsing history follows the new game
+    for conn in state.connections.lock().await.values_mut() {
+        conn.viewing = None;
+    }
+
+    ws::broadcast_state(&state).await;
+
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        ws::broadcast_ownership(&state_clone).await;
+    });
+    tokio::spawn(ws::ai_turn(state.clone())); // The loaded position may have the AI to move
+
+    (StatusCode::OK, "Game loaded".to_string())
+}
+
+/// SGF `RU` value for a ruleset (custom rules have no standard name)
+fn ruleset_to_sgf(ruleset: &Ruleset) -> Option<&'static str> {
+    if !ruleset.is_preset() {
+        return None;
+    }
+
+    match ruleset.name {
+        RulesetName::Japanese => Some("Japanese"),
+        RulesetName::Chinese => Some("Chinese"),
+        RulesetName::Aga => Some("AGA"),
+        RulesetName::Korean => Some("Korean"),
+        RulesetName::NewZealand => Some("NZ"),
+        RulesetName::TrompTaylor => Some("Tromp-Taylor"),
+        RulesetName::Custom => None,
+    }
+}
+
+/// Recognize common `RU` spellings; unknown rules fall back to the default ruleset
+fn ruleset_from_sgf(value: &str) -> Option<RulesetName> {
+    match value.trim().to_lowercase().as_str() {
+        "japanese" | "jp" => Some(RulesetName::Japanese),
+        "chinese" | "cn" => Some(RulesetName::Chinese),
+        "aga" => Some(RulesetName::Aga),
+        "korean" | "kr" => Some(RulesetName::Korean),
+        "nz" | "new zealand" | "new-zealand" => Some(RulesetName::NewZealand),
+        "tromp-taylor" | "tromp taylor" | "tt" => Some(RulesetName::TrompTaylor),
+        _ => None,
+    }
+}
+
+fn color_tag(color: Color) -> &'static str {
+    match color {
+        Color::Black => "B",
+        Color::White => "W",
+    }
+}
+
+/// Convert position to SGF point (e.g., (3, 15) -> "dp"); "aa" is the top-left corner
+fn point(pos: Position) -> String {
+    let coord = |v: usize| (b'a' + v as u8) as char;
+    format!("{}{}", coord(pos.x), coord(pos.y))
+}
+
+/// Escape text property values (`]` and `\` must be backslash-escaped)
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == ']' || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GamePhase;
+
+    #[test]
+    fn test_point_conversion() {
+        assert_eq!(point(Position::new(0, 0)), "aa");
+        assert_eq!(point(Position::new(3, 15)), "dp");
+        assert_eq!(point(Position::new(18, 18)), "ss");
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("plain"), "plain");
+        assert_eq!(escape("a]b\\c"), "a\\]b\\\\c");
+    }
+
+    #[test]
+    fn test_write_header() {
+        let game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        let sgf = write(&game);
+
+        assert!(sgf.starts_with("(;FF[4]GM[1]CA[UTF-8]"));
+        assert!(sgf.contains("SZ[9]"));
+        assert!(sgf.contains("KM[7.5]"));
+        assert!(sgf.contains("RU[Chinese]"));
+        assert!(!sgf.contains("PB["));
+        assert!(sgf.trim_end().ends_with(')'));
+    }
+
+    #[test]
+    fn test_write_moves_and_passes() {
+        let mut game = Game::new();
+        game.set_player_name(Color::Black, "Ann [5k]".to_string());
+        game.place_stone(Position::new(3, 3), Color::Black).unwrap();
+        game.place_stone(Position::new(15, 15), Color::White).unwrap();
+        game.pass(Color::Black).unwrap();
+
+        let sgf = write(&game);
+
+        assert!(sgf.contains("PB[Ann [5k\\]]"));
+        assert!(!sgf.contains("PW["));
+        assert!(sgf.contains(";B[dd]\n;W[pp]\n;B[])"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut game = Game::with_size(BoardSize::square(13).unwrap(), Ruleset::preset(RulesetName::Japanese));
+        game.set_player_name(Color::White, "Bo".to_string());
+        game.place_stone(Position::new(3, 3), Color::Black).unwrap();
+        game.place_stone(Position::new(9, 9), Color::White).unwrap();
+        game.pass(Color::Black).unwrap();
+        game.place_stone(Position::new(3, 9), Color::White).unwrap();
+
+        let loaded = parse(&write(&game)).unwrap();
+
+        assert_eq!(loaded.get_board_size(), BoardSize::square(13).unwrap());
+        assert_eq!(loaded.get_ruleset(), game.get_ruleset());
+        assert_eq!(loaded.get_komi(), 6.5);
+        assert_eq!(loaded.get_player_name(Color::White), Some("Bo"));
+        assert_eq!(loaded.moves(), game.moves());
+        assert_eq!(loaded.get_board(), game.get_board());
+        assert_eq!(loaded.get_turn(), Color::Black);
+    }
+
+    #[test]
+    fn test_score_round_trip() {
+        // Black walls off the left of a 5x5 board; White's invasion at aa is agreed dead
+        let mut game = Game::with_size(BoardSize::square(5).unwrap(), Ruleset::preset(RulesetName::Japanese));
+        for y in 0..5 {
+            game.place_stone(Position::new(2, y), Color::Black).unwrap();
+            game.place_stone(Position::new(3, y), Color::White).unwrap();
+        }
+        game.pass(Color::Black).unwrap();
+        game.place_stone(Position::new(0, 0), Color::White).unwrap();
+        game.pass(Color::Black).unwrap();
+        game.pass(Color::White).unwrap();
+        game.toggle_dead(Position::new(0, 0)).unwrap();
+        game.accept_score(Color::Black).unwrap();
+        game.accept_score(Color::White).unwrap();
+
+        let sgf = write(&game);
+        assert!(sgf.contains("TB[aa]"));
+        assert!(sgf.contains("TW[ea]"));
+
+        let loaded = parse(&sgf).unwrap();
+        assert_eq!(loaded.get_phase(), GamePhase::Finished);
+        assert_eq!(loaded.get_result(), game.get_result());
+        assert_eq!(loaded.get_dead_stones(), game.get_dead_stones());
+        assert_eq!(loaded.get_final_score(), game.get_final_score());
+    }
+
+    #[test]
+    fn test_parse_result() {
+        assert_eq!(parse_result("B+R"), Some(GameResult::Resignation { winner: Color::Black }));
+        assert_eq!(parse_result("W+12.5"), Some(GameResult::Score { winner: Color::White, margin: 12.5 }));
+        assert_eq!(parse_result("w+Time"), Some(GameResult::Time { winner: Color::White }));
+        assert_eq!(parse_result("B+F"), Some(GameResult::Forfeit { winner: Color::Black }));
+        assert_eq!(parse_result("Draw"), Some(GameResult::Jigo));
+        assert_eq!(parse_result("B+"), None);
+        assert_eq!(parse_result("Void"), None);
+    }
+
+    #[test]
+    fn test_resignation_round_trip() {
+        let mut game = Game::new();
+        game.place_stone(Position::new(3, 3), Color::Black).unwrap();
+        game.resign(Color::White).unwrap();
+
+        let sgf = write(&game);
+        assert!(sgf.contains("RE[B+R]"));
+
+        let loaded = parse(&sgf).unwrap();
+        assert_eq!(loaded.get_result(), game.get_result());
+        assert_eq!(loaded.get_phase(), GamePhase::Finished);
+    }
+
+    #[test]
+    fn test_rectangular_board() {
+        let mut game = Game::with_size(BoardSize::new(13, 7).unwrap(), Ruleset::default());
+        game.place_stone(Position::new(12, 6), Color::Black).unwrap();
+
+        let sgf = write(&game);
+        assert!(sgf.contains("SZ[13:7]"));
+
+        let loaded = parse(&sgf).unwrap();
+        assert_eq!(loaded.get_board_size(), game.get_board_size());
+        assert_eq!(loaded.get_board(), game.get_board());
+
+        assert!(parse("(;SZ[13:7];B[am])").is_err()); // Row 12 only exists on the wider axis
+        assert!(parse("(;SZ[13:1])").is_err());
+    }
+
+    #[test]
+    fn test_parse_setup_properties() {
+        let sgf = "(;GM[1]FF[4]SZ[9]KM[0.5]HA[2]AB[cc][gg]AW[aa:ba];AE[ba];W[ee];B[ge])";
+        let game = parse(sgf).unwrap();
+        let board = game.get_board();
+
+        assert_eq!(game.get_komi(), 0.5);
+        assert_eq!(game.get_handicap(), 2);
+        assert_eq!(board[2][2], Some(Color::Black));
+        assert_eq!(board[6][6], Some(Color::Black));
+        assert_eq!(board[0][0], Some(Color::White));
+        assert_eq!(board[0][1], None);
+        assert_eq!(board[4][4], Some(Color::White));
+        assert_eq!(game.moves().len(), 2);
+        assert_eq!(game.get_turn(), Color::White);
+
+        // Setup and PL survive a round trip
+        let reloaded = parse(&write(&game)).unwrap();
+        assert_eq!(reloaded.get_board(), board);
+        assert_eq!(reloaded.moves(), game.moves());
+    }
+
+    #[test]
+    fn test_parse_player_to_move() {
+        let game = parse("(;SZ[9]AB[cc]PL[W])").unwrap();
+        assert_eq!(game.get_turn(), Color::White);
+    }
+
+    #[test]
+    fn test_parse_follows_main_line() {
+        let sgf = "(;SZ[9];B[cc](;W[gg];B[tt])(;W[cg]))";
+        let game = parse(sgf).unwrap();
+
+        assert_eq!(game.moves().len(), 3);
+        assert_eq!(game.moves()[1].kind, MoveKind::Stone(Position::new(6, 6)));
+        assert_eq!(game.moves()[2].kind, MoveKind::Pass);
+    }
+
+    #[test]
+    fn test_parse_text_escapes() {
+        let game = parse("(;PB[Ann [5k\\]]PW[Bo\\\\])").unwrap();
+        assert_eq!(game.get_player_name(Color::Black), Some("Ann [5k]"));
+        assert_eq!(game.get_player_name(Color::White), Some("Bo\\"));
+    }
+
+    #[test]
+    fn test_parse_rejects_illegal_moves() {
+        let occupied = parse("(;SZ[9];B[cc];W[cc])").err().unwrap();
+        assert!(occupied.contains("W[cc]"), "{}", occupied);
+        assert!(occupied.contains("Intersection occupied"), "{}", occupied);
+
+        let wrong_turn = parse("(;SZ[9];B[cc];B[dd])").err().unwrap();
+        assert!(wrong_turn.contains("Not your turn"), "{}", wrong_turn);
+
+        let off_board = parse("(;SZ[9];B[jj])").err().unwrap();
+        assert!(off_board.contains("off the board"), "{}", off_board);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_input() {
+        assert!(parse("").is_err());
+        assert!(parse("(;SZ[9]").is_err());
+        assert!(parse("(;SZ[9];B[cc").is_err());
+        assert!(parse("(;SZ[99])").is_err());
+        assert!(parse("(;SZ[9];B[cc];AB[dd])").is_err());
+    }
+}
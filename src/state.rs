@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::{Mutex, mpsc};
+use crate::clock::Clock;
 use crate::game::{Game, Color};
-use crate::katago::{KataGoService, KataGoConfig};
+use crate::katago::{AiStrength, Evaluation, KataGoService, KataGoConfig};
 
 /// Unique identifier for each WebSocket connection
 pub type ConnectionId = u64;
@@ -11,6 +13,14 @@ pub type ConnectionId = u64;
 pub struct PlayerConnection {
     pub color: Option<Color>,
     pub sender: mpsc::UnboundedSender<String>, // Channel to send messages to this connection
+    pub viewing: Option<usize>, // Move number being browsed, None when following the live game
+}
+
+/// A color played by KataGo instead of a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AiSeat {
+    pub color: Color,
+    pub strength: AiStrength,
 }
 
 /// Shared application state
@@ -18,7 +28,11 @@ pub struct AppState {
     pub game: Arc<Mutex<Game>>,
     pub connections: Arc<Mutex<HashMap<ConnectionId, PlayerConnection>>>,
     pub next_connection_id: Arc<Mutex<ConnectionId>>,
-    pub katago: Arc<Mutex<Option<KataGoService>>>,
+    pub katago: Option<KataGoService>, // Shared by concurrent queries; no lock needed
+    pub clock: Arc<Mutex<Option<Clock>>>, // None for untimed games
+    pub ai: Arc<Mutex<Option<AiSeat>>>,
+    pub evaluations: Arc<Mutex<BTreeMap<usize, Evaluation>>>, // KataGo's judgement by move number
+    pub hints_enabled: Arc<Mutex<bool>>, // Room setting, opt-in per game
 }
 
 impl AppState {
@@ -40,7 +54,11 @@ impl AppState {
             game: Arc::new(Mutex::new(Game::new())),
             connections: Arc::new(Mutex::new(HashMap::new())),
             next_connection_id: Arc::new(Mutex::new(0)),
-            katago: Arc::new(Mutex::new(katago_service)),
+            katago: katago_service,
+            clock: Arc::new(Mutex::new(None)),
+            ai: Arc::new(Mutex::new(None)),
+            evaluations: Arc::new(Mutex::new(BTreeMap::new())),
+            hints_enabled: Arc::new(Mutex::new(false)),
         }
     }
 
@@ -53,7 +71,7 @@ impl AppState {
     }
 
     /// Check if KataGo service is available
-    pub async fn has_katago(&self) -> bool {
-        self.katago.lock().await.is_some()
+    pub fn has_katago(&self) -> bool {
+        self.katago.is_some()
     }
 }
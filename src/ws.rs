@@ -2,12 +2,20 @@ use axum::{
     extract::{ws::{WebSocket, WebSocketUpgrade, Message}, State},
     response::Response,
 };
-use futures::{sink::SinkExt, stream::StreamExt};
+use futures::{future::BoxFuture, sink::SinkExt, stream::StreamExt};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::mpsc;
-use crate::game::{Color, Position};
-use crate::state::{AppState, PlayerConnection};
+use crate::clock::{Clock, ClockState, TimeControl};
+use crate::game::{BoardSize, Color, Game, GamePhase, GameEvent, GameResult, Group, HandicapPlacement, Move, MoveError, MoveOutcome, Position, Ruleset, RulesetChoice, Score};
+use crate::katago::{AiStrength, Evaluation, Hint};
+use crate::state::{AiSeat, AppState, PlayerConnection};
+
+/// Suggested moves sent when a hint request does not say how many
+const DEFAULT_HINTS: usize = 3;
+/// Upper bound on suggested moves per hint request
+const MAX_HINTS: usize = 10;
 
 /// Messages sent from client to server
 #[derive(Debug, Deserialize)]
@@ -15,10 +23,51 @@ use crate::state::{AppState, PlayerConnection};
 enum ClientMessage {
     ChooseColor {
         color: Color,
+        #[serde(default)]
+        name: Option<String>,
     },
     Move { x: usize, y: usize },
     Pass,
-    Reset { board_size: usize },
+    Resign,
+    SeatAi {
+        color: Color,
+        #[serde(default)]
+        strength: AiStrength,
+    },
+    RemoveAi,
+    Reset {
+        board_size: BoardSize, // A number for square boards, or {width, height}
+        #[serde(default)]
+        rules: Option<RulesetChoice>,
+        #[serde(default)]
+        komi: Option<f32>,
+        #[serde(default)]
+        handicap: u8,
+        #[serde(default)]
+        handicap_placement: HandicapPlacement,
+        #[serde(default)]
+        time_control: Option<TimeControl>, // Untimed when absent
+        #[serde(default)]
+        hints: bool, // Opt-in; leave off for rated games
+    },
+    ViewMove { move_number: usize },
+    ViewBack,
+    ViewForward,
+    ViewLive,
+    Preview {
+        x: usize,
+        y: usize,
+        #[serde(default)]
+        color: Option<Color>, // Defaults to the player's own color, or the side to move
+    },
+    ToggleDead { x: usize, y: usize },
+    AcceptScore,
+    ResumePlay,
+    GetEvaluations,
+    RequestHints {
+        #[serde(default)]
+        count: Option<usize>, // Defaults to DEFAULT_HINTS
+    },
 }
 
 /// Messages sent from server to client
@@ -27,24 +76,116 @@ enum ClientMessage {
 enum ServerMessage {
     State {
         board: Vec<Vec<Option<Color>>>,
-        board_size: usize,
+        board_size: BoardSize,
+        groups: Vec<Group>,
+        rules: Ruleset,
+        handicap: u8,
+        handicap_to_place: u8,
         turn: Color,
+        legal_moves: Vec<Position>, // Where the player to move may play
         prisoners: Prisoners,
         players: Players,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ai: Option<AiSeat>,
         passes: u8,
+        phase: GamePhase,
+        dead_stones: Vec<Position>,
+        score_accepted: ScoreAccepted,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        score: Option<Score>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        result: Option<GameResult>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        clock: Option<ClockState>,
         #[serde(skip_serializing_if = "Option::is_none")]
         ownership: Option<Vec<Vec<f32>>>,
         katago_available: bool,
+        hints_enabled: bool,
     },
     OwnershipUpdate {
         ownership: Vec<Vec<f32>>,
     },
+    EvaluationUpdate {
+        move_number: usize,
+        #[serde(flatten)]
+        evaluation: Evaluation,
+    },
+    Evaluations {
+        series: Vec<MoveEvaluation>, // Ordered by move number, for a win-probability graph
+    },
+    Hints {
+        move_number: usize, // Position the hints are for, so stale ones can be dropped
+        hints: Vec<Hint>,
+    },
+    ClockTick {
+        clock: ClockState,
+    },
+    Events {
+        events: Vec<GameEvent>,
+    },
+    Preview {
+        position: Position,
+        color: Color,
+        legal: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<&'static str>, // Error code the move would be rejected with
+        #[serde(skip_serializing_if = "Option::is_none")]
+        board: Option<Vec<Vec<Option<Color>>>>,
+        captures: Vec<Position>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        ko: Option<Position>,
+    },
+    MovePlayed {
+        color: Color,
+        #[serde(flatten)]
+        outcome: MoveOutcome,
+    },
     Error {
+        code: &'static str, // Stable identifier clients can branch on, e.g. "occupied"
         message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        position: Option<Position>,
     },
     YourColor {
         color: Option<Color>,
     },
+    HistoryView {
+        move_number: usize,
+        total_moves: usize,
+        live: bool,
+        board: Vec<Vec<Option<Color>>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        last_move: Option<Move>,
+    },
+}
+
+/// KataGo's evaluation of the position after a given move
+#[derive(Debug, Serialize)]
+struct MoveEvaluation {
+    move_number: usize,
+    #[serde(flatten)]
+    evaluation: Evaluation,
+}
+
+/// Options chosen when a new game is started
+struct RoomSettings {
+    time_control: Option<TimeControl>,
+    hints: bool,
+}
+
+/// A request made while settling the score
+enum ScoringAction {
+    ToggleDead(Position),
+    Accept,
+    Resume,
+}
+
+/// Where a history browsing request should move the viewer
+enum Navigation {
+    Goto(usize),
+    Back,
+    Forward,
+    Live,
 }
 
 #[derive(Debug, Serialize)]
@@ -59,6 +200,12 @@ struct Players {
     white: bool,
 }
 
+#[derive(Debug, Serialize)]
+struct ScoreAccepted {
+    black: bool,
+    white: bool,
+}
+
 /// WebSocket connection handler
 pub async fn handler(ws: WebSocketUpgrade, State(state): State<Arc<AppState>>) -> Response {
     ws.on_upgrade(|socket| handle_socket(socket, state))
@@ -81,6 +228,7 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
         connections.insert(conn_id, PlayerConnection {
             color: None,
             sender: tx,
+            viewing: None,
         });
     }
 
@@ -123,14 +271,23 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
     broadcast_state(&state).await;
 }
 
-/// Broadcast current game state to all connections
-async fn broadcast_state(state: &AppState) {
-    let game = state.game.lock().await;
+/// Broadcast current game state to all connections, preceded by any new game events
+pub(crate) async fn broadcast_state(state: &AppState) {
+    let mut game = state.game.lock().await;
     let connections = state.connections.lock().await;
 
-    // Determine which colors are assigned
-    let mut black_assigned = false;
-    let mut white_assigned = false;
+    let events = game.take_events();
+    if !events.is_empty() {
+        let json = serde_json::to_string(&ServerMessage::Events { events }).unwrap();
+        for conn in connections.values() {
+            let _ = conn.sender.send(json.clone());
+        }
+    }
+
+    // Determine which colors are assigned (the AI seat counts)
+    let ai = *state.ai.lock().await;
+    let mut black_assigned = ai.is_some_and(|seat| seat.color == Color::Black);
+    let mut white_assigned = ai.is_some_and(|seat| seat.color == Color::White);
 
     for conn in connections.values() {
         if conn.color == Some(Color::Black) {
@@ -142,12 +299,18 @@ async fn broadcast_state(state: &AppState) {
     }
 
     // Check if KataGo is available
-    let katago_available = state.katago.lock().await.is_some();
+    let katago_available = state.has_katago();
+    let clock = state.clock.lock().await.as_ref().map(|clock| clock.state(Instant::now()));
 
     let msg = ServerMessage::State {
         board: game.get_board(),
         board_size: game.get_board_size(),
+        groups: game.groups(),
+        rules: *game.get_ruleset(),
+        handicap: game.get_handicap(),
+        handicap_to_place: game.get_handicap_to_place(),
         turn: game.get_turn(),
+        legal_moves: game.legal_moves(),
         prisoners: Prisoners {
             black: game.get_prisoners().0,
             white: game.get_prisoners().1,
@@ -156,9 +319,24 @@ async fn broadcast_state(state: &AppState) {
             black: black_assigned,
             white: white_assigned,
         },
-        passes: 0, // TODO: Track consecutive passes
+        ai,
+        passes: game.get_passes(),
+        phase: game.get_phase(),
+        dead_stones: game.get_dead_stones().iter().copied().collect(),
+        score_accepted: ScoreAccepted {
+            black: game.get_score_accepted().0,
+            white: game.get_score_accepted().1,
+        },
+        score: match game.get_phase() {
+            GamePhase::Playing => None,
+            GamePhase::Scoring => Some(game.score()),
+            GamePhase::Finished => game.get_final_score(),
+        },
+        result: game.get_result(),
+        clock,
         ownership: None, // Ownership sent separately via broadcast_ownership
         katago_available,
+        hints_enabled: *state.hints_enabled.lock().await,
     };
 
     let json = serde_json::to_string(&msg).unwrap();
@@ -170,51 +348,81 @@ async fn broadcast_state(state: &AppState) {
 }
 
 /// Broadcast ownership data asynchronously (non-blocking)
-async fn broadcast_ownership(state: &AppState) {
+pub(crate) async fn broadcast_ownership(state: &AppState) {
     // Check if KataGo is available
-    let katago_available = {
-        let katago_guard = state.katago.lock().await;
-        katago_guard.is_some()
-    };
-
-    if !katago_available {
+    let Some(katago_service) = state.katago.as_ref() else {
         return;
-    }
-
-    // Get current board state
-    let (board, board_size) = {
-        let game = state.game.lock().await;
-        (game.get_board(), game.get_board_size())
     };
 
+    // Snapshot the game so the query does not hold the lock
+    let game = state.game.lock().await.clone();
+
     // Calculate ownership (this may take time)
-    let ownership = {
-        let mut katago_guard = state.katago.lock().await;
-        if let Some(katago_service) = katago_guard.as_mut() {
-            match katago_service.get_ownership(&board, board_size) {
-                Ok(ownership_data) => Some(ownership_data.ownership),
-                Err(e) => {
-                    eprintln!("Failed to get ownership data: {}", e);
-                    None
-                }
-            }
-        } else {
-            None
+    let ownership_data = match katago_service.get_ownership(&game).await {
+        Ok(ownership_data) => ownership_data,
+        Err(e) => {
+            eprintln!("Failed to get ownership data: {}", e);
+            return;
         }
     };
 
-    // If we got ownership data, broadcast it
-    if let Some(ownership_data) = ownership {
-        let msg = ServerMessage::OwnershipUpdate {
-            ownership: ownership_data,
-        };
+    // Queries finish out of order: drop both results if the game has moved on meanwhile
+    let current = state.game.lock().await;
+    if !same_position(&current, &game) {
+        return;
+    }
+    let move_number = game.moves().len();
+    if let Some(evaluation) = ownership_data.evaluation {
+        state.evaluations.lock().await.insert(move_number, evaluation);
+    }
+    drop(current);
 
-        let json = serde_json::to_string(&msg).unwrap();
+    broadcast(state, &ServerMessage::OwnershipUpdate { ownership: ownership_data.ownership }).await;
+    if let Some(evaluation) = ownership_data.evaluation {
+        broadcast(state, &ServerMessage::EvaluationUpdate { move_number, evaluation }).await;
+    }
+}
 
-        let connections = state.connections.lock().await;
-        for conn in connections.values() {
-            let _ = conn.sender.send(json.clone());
+/// Send the recorded evaluations to a specific connection
+async fn send_evaluations(state: &AppState, conn_id: u64) {
+    let series = state.evaluations.lock().await
+        .iter()
+        .map(|(&move_number, &evaluation)| MoveEvaluation { move_number, evaluation })
+        .collect();
+    send_to(state, conn_id, &ServerMessage::Evaluations { series }).await;
+}
+
+/// Broadcast remaining time on both clocks
+pub(crate) async fn broadcast_clock(state: &AppState, clock: ClockState) {
+    broadcast(state, &ServerMessage::ClockTick { clock }).await;
+}
+
+/// Send a message to every connection
+async fn broadcast(state: &AppState, msg: &ServerMessage) {
+    let json = serde_json::to_string(msg).unwrap();
+
+    let connections = state.connections.lock().await;
+    for conn in connections.values() {
+        let _ = conn.sender.send(json.clone());
+    }
+}
+
+/// Bring the clock up to date with the game, ending it on time if the player to move ran out
+///
+/// Returns true if the game was lost on time.
+async fn settle_clock(state: &AppState, game: &mut Game) -> bool {
+    let mut clock = state.clock.lock().await;
+    let Some(clock) = clock.as_mut() else {
+        return false;
+    };
+
+    let now = Instant::now();
+    match clock.check(now).or_else(|| clock.follow(game, now)) {
+        Some(loser) => {
+            game.end(GameResult::Time { winner: loser.opposite() });
+            true
         }
+        None => false,
     }
 }
 
@@ -233,12 +441,26 @@ async fn send_your_color(state: &AppState, conn_id: u64) {
 }
 
 /// Send error message to a specific connection
-async fn send_error(state: &AppState, conn_id: u64, message: String) {
+async fn send_error(state: &AppState, conn_id: u64, code: &'static str, message: String) {
+    send_to(state, conn_id, &ServerMessage::Error { code, message, position: None }).await;
+}
+
+/// Tell a specific connection why its move or pass was rejected
+async fn send_move_error(state: &AppState, conn_id: u64, error: MoveError) {
+    let msg = ServerMessage::Error {
+        code: error.code(),
+        message: error.to_string(),
+        position: error.position(),
+    };
+    send_to(state, conn_id, &msg).await;
+}
+
+/// Send a message to a specific connection
+async fn send_to(state: &AppState, conn_id: u64, msg: &ServerMessage) {
     let connections = state.connections.lock().await;
 
     if let Some(conn) = connections.get(&conn_id) {
-        let msg = ServerMessage::Error { message };
-        let json = serde_json::to_string(&msg).unwrap();
+        let json = serde_json::to_string(msg).unwrap();
         let _ = conn.sender.send(json);
     }
 }
@@ -248,14 +470,14 @@ async fn handle_message(state: &Arc<AppState>, conn_id: u64, text: &str) {
     let client_msg: ClientMessage = match serde_json::from_str(text) {
         Ok(msg) => msg,
         Err(e) => {
-            send_error(state, conn_id, format!("Invalid message: {}", e)).await;
+            send_error(state, conn_id, "invalid_message", format!("Invalid message: {}", e)).await;
             return;
         }
     };
 
     match client_msg {
-        ClientMessage::ChooseColor { color } => {
-            handle_choose_color(state, conn_id, color).await;
+        ClientMessage::ChooseColor { color, name } => {
+            handle_choose_color(state, conn_id, color, name).await;
         }
         ClientMessage::Move { x, y } => {
             handle_move(state, conn_id, x, y).await;
@@ -263,22 +485,68 @@ async fn handle_message(state: &Arc<AppState>, conn_id: u64, text: &str) {
         ClientMessage::Pass => {
             handle_pass(state, conn_id).await;
         }
-        ClientMessage::Reset { board_size } => {
-            handle_reset(state, board_size).await;
+        ClientMessage::Resign => {
+            handle_resign(state, conn_id).await;
+        }
+        ClientMessage::SeatAi { color, strength } => {
+            handle_seat_ai(state, conn_id, color, strength).await;
+        }
+        ClientMessage::RemoveAi => {
+            *state.ai.lock().await = None;
+            broadcast_state(state).await;
+        }
+        ClientMessage::Reset { board_size, rules, komi, handicap, handicap_placement, time_control, hints } => {
+            let mut ruleset = rules.map_or_else(Ruleset::default, RulesetChoice::into_ruleset);
+            if let Some(komi) = komi {
+                ruleset.komi = komi;
+            }
+            let settings = RoomSettings { time_control, hints };
+            handle_reset(state, conn_id, board_size, ruleset, handicap, handicap_placement, settings).await;
+        }
+        ClientMessage::ViewMove { move_number } => {
+            handle_view(state, conn_id, Navigation::Goto(move_number)).await;
+        }
+        ClientMessage::ViewBack => {
+            handle_view(state, conn_id, Navigation::Back).await;
+        }
+        ClientMessage::ViewForward => {
+            handle_view(state, conn_id, Navigation::Forward).await;
+        }
+        ClientMessage::ViewLive => {
+            handle_view(state, conn_id, Navigation::Live).await;
+        }
+        ClientMessage::Preview { x, y, color } => {
+            handle_preview(state, conn_id, Position::new(x, y), color).await;
+        }
+        ClientMessage::ToggleDead { x, y } => {
+            handle_scoring(state, conn_id, ScoringAction::ToggleDead(Position::new(x, y))).await;
+        }
+        ClientMessage::AcceptScore => {
+            handle_scoring(state, conn_id, ScoringAction::Accept).await;
+        }
+        ClientMessage::ResumePlay => {
+            handle_scoring(state, conn_id, ScoringAction::Resume).await;
+        }
+        ClientMessage::GetEvaluations => {
+            send_evaluations(state, conn_id).await;
+        }
+        ClientMessage::RequestHints { count } => {
+            handle_hints(state, conn_id, count.unwrap_or(DEFAULT_HINTS)).await;
         }
     }
 }
 
 /// Handle color selection
-async fn handle_choose_color(state: &AppState, conn_id: u64, color: Color) {
+async fn handle_choose_color(state: &AppState, conn_id: u64, color: Color, name: Option<String>) {
     let mut connections = state.connections.lock().await;
 
     // Check if color is already taken
-    let color_taken = connections.values().any(|conn| conn.color == Some(color));
+    let ai_color = state.ai.lock().await.map(|seat| seat.color);
+    let color_taken = ai_color == Some(color) || connections.values().any(|conn| conn.color == Some(color));
 
     if color_taken {
         drop(connections);
-        send_error(state, conn_id, "Color already taken".to_string()).await;
+        send_error(state, conn_id, "color_taken", "Color already taken".to_string()).await;
         return;
     }
 
@@ -289,6 +557,16 @@ async fn handle_choose_color(state: &AppState, conn_id: u64, color: Color) {
 
     drop(connections);
 
+    let mut game = state.game.lock().await;
+
+    // Record the player's name for the game record
+    if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+        game.set_player_name(color, name);
+    }
+
+    start_clock_if_seated(state, &game).await;
+    drop(game);
+
     // Broadcast updated state
     broadcast_state(state).await;
     send_your_color(state, conn_id).await;
@@ -296,92 +574,454 @@ async fn handle_choose_color(state: &AppState, conn_id: u64, color: Color) {
 
 /// Handle move attempt
 async fn handle_move(state: &Arc<AppState>, conn_id: u64, x: usize, y: usize) {
-    let connections = state.connections.lock().await;
+    let Some(color) = player_color(state, conn_id).await else {
+        return;
+    };
 
-    // Get player's color
-    let color = match connections.get(&conn_id).and_then(|c| c.color) {
-        Some(c) => c,
-        None => {
-            drop(connections);
-            send_error(state, conn_id, "You must choose a color first".to_string()).await;
-            return;
-        }
+    if let Err(e) = play(state, color, Some(Position::new(x, y))).await {
+        send_move_error(state, conn_id, e).await;
+    }
+}
+
+/// Handle pass
+async fn handle_pass(state: &Arc<AppState>, conn_id: u64) {
+    let Some(color) = player_color(state, conn_id).await else {
+        return;
     };
 
-    drop(connections);
+    if let Err(e) = play(state, color, None).await {
+        send_move_error(state, conn_id, e).await;
+    }
+}
 
-    // Attempt move
-    let mut game = state.game.lock().await;
-    let result = game.place_stone(Position::new(x, y), color);
+/// The color a connection plays, or None after telling it to choose one first
+async fn player_color(state: &AppState, conn_id: u64) -> Option<Color> {
+    let color = state.connections.lock().await.get(&conn_id).and_then(|c| c.color);
+    if color.is_none() {
+        send_error(state, conn_id, "no_color", "You must choose a color first".to_string()).await;
+    }
+    color
+}
 
+/// Play a stone (or pass when `pos` is None) for `color`, charging the thinking time to the mover
+async fn play(state: &Arc<AppState>, color: Color, pos: Option<Position>) -> Result<(), MoveError> {
+    let mut game = state.game.lock().await;
+    let timed_out = settle_clock(state, &mut game).await;
+    let result = match pos {
+        Some(pos) => game.place_stone(pos, color).map(Some),
+        None => game.pass(color).map(|()| None),
+    };
+    if result.is_ok() {
+        settle_clock(state, &mut game).await;
+    }
     drop(game);
 
-    match result {
-        Ok(()) => {
-            broadcast_state(state).await;
-
-            // Spawn async task to calculate and broadcast ownership (non-blocking)
-            let state_clone = state.clone();
-            tokio::spawn(async move {
-                broadcast_ownership(&state_clone).await;
-            });
-        }
+    let outcome = match result {
+        Ok(outcome) => outcome,
         Err(e) => {
-            send_error(state, conn_id, e).await;
+            if timed_out {
+                broadcast_state(state).await;
+            }
+            return Err(e);
         }
+    };
+
+    if let Some(outcome) = outcome {
+        broadcast(state, &ServerMessage::MovePlayed { color, outcome }).await;
     }
+    broadcast_state(state).await;
+
+    // Spawn async task to calculate and broadcast ownership (non-blocking)
+    let state_clone = state.clone();
+    tokio::spawn(async move {
+        broadcast_ownership(&state_clone).await;
+    });
+
+    tokio::spawn(ai_turn(state.clone()));
+    Ok(())
 }
 
-/// Handle pass
-async fn handle_pass(state: &Arc<AppState>, conn_id: u64) {
-    let connections = state.connections.lock().await;
+/// Let the AI seat move if it is its turn
+///
+/// Boxed because the AI's own move goes back through `play`, which spawns this again.
+pub(crate) fn ai_turn(state: Arc<AppState>) -> BoxFuture<'static, ()> {
+    Box::pin(async move {
+        let Some(seat) = *state.ai.lock().await else {
+            return;
+        };
 
-    // Get player's color
-    let color = match connections.get(&conn_id).and_then(|c| c.color) {
-        Some(c) => c,
-        None => {
-            drop(connections);
-            send_error(state, conn_id, "You must choose a color first".to_string()).await;
+        // Snapshot the game so the query does not hold the lock
+        let game = state.game.lock().await.clone();
+        if !is_ai_turn(&game, seat) {
             return;
         }
-    };
 
-    drop(connections);
+        let Some(katago_service) = state.katago.as_ref() else {
+            return;
+        };
+        let choice = katago_service.get_ai_move(&game, seat.strength).await;
 
-    let mut game = state.game.lock().await;
+        // Nothing to do if the game moved on, was replaced or lost its AI seat while KataGo was thinking
+        if !ai_move_wanted(&state, &game, seat).await {
+            return;
+        }
 
-    // Check if it's their turn
-    if game.get_turn() != color {
-        drop(game);
-        send_error(state, conn_id, "Not your turn".to_string()).await;
+        let pos = choice.unwrap_or_else(|e| {
+            eprintln!("AI move failed, passing: {}", e);
+            None
+        });
+        if let Err(e) = play(&state, seat.color, pos).await {
+            if !ai_move_wanted(&state, &game, seat).await {
+                return;
+            }
+            eprintln!("AI move {:?} rejected, passing: {}", pos, e);
+            let _ = play(&state, seat.color, None).await;
+        }
+    })
+}
+
+/// Whether the AI's answer for the `snapshot` position is still due from `seat`
+async fn ai_move_wanted(state: &AppState, snapshot: &Game, seat: AiSeat) -> bool {
+    let current = state.game.lock().await;
+    let ai = *state.ai.lock().await;
+    ai == Some(seat) && is_ai_turn(&current, seat) && same_position(&current, snapshot)
+}
+
+/// Whether it is `seat`'s move in a game still being played
+fn is_ai_turn(game: &Game, seat: AiSeat) -> bool {
+    game.get_phase() == GamePhase::Playing && game.get_turn() == seat.color
+}
+
+/// Whether `current` is still the game `snapshot` was taken from, at the same position
+fn same_position(current: &Game, snapshot: &Game) -> bool {
+    current.get_board_size() == snapshot.get_board_size()
+        && current.get_setup() == snapshot.get_setup()
+        && current.moves() == snapshot.moves()
+}
+
+/// Hand a color to KataGo
+async fn handle_seat_ai(state: &Arc<AppState>, conn_id: u64, color: Color, strength: AiStrength) {
+    if !state.has_katago() {
+        send_error(state, conn_id, "katago_unavailable", "KataGo is not available".to_string()).await;
         return;
     }
 
-    game.pass();
+    let taken = state.connections.lock().await.values().any(|conn| conn.color == Some(color));
+    if taken {
+        send_error(state, conn_id, "color_taken", "Color already taken".to_string()).await;
+        return;
+    }
+
+    *state.ai.lock().await = Some(AiSeat { color, strength });
+
+    let game = state.game.lock().await;
+    start_clock_if_seated(state, &game).await;
     drop(game);
 
     broadcast_state(state).await;
+    tokio::spawn(ai_turn(state.clone()));
+}
 
-    // Spawn async task to calculate and broadcast ownership (non-blocking)
-    let state_clone = state.clone();
+/// Send KataGo's suggested moves to the requesting connection only
+async fn handle_hints(state: &Arc<AppState>, conn_id: u64, count: usize) {
+    if player_color(state, conn_id).await.is_none() {
+        return; // Spectators get no hints
+    }
+    if !*state.hints_enabled.lock().await {
+        send_error(state, conn_id, "hints_disabled", "Hints are disabled for this game".to_string()).await;
+        return;
+    }
+    if !state.has_katago() {
+        send_error(state, conn_id, "katago_unavailable", "KataGo is not available".to_string()).await;
+        return;
+    }
+
+    // Analyse in the background so the connection keeps handling messages
+    let state = state.clone();
     tokio::spawn(async move {
-        broadcast_ownership(&state_clone).await;
+        let Some(katago_service) = state.katago.as_ref() else {
+            return;
+        };
+        let game = state.game.lock().await.clone();
+
+        match katago_service.get_hints(&game, count.min(MAX_HINTS)).await {
+            Ok(hints) => {
+                let move_number = game.moves().len();
+                send_to(&state, conn_id, &ServerMessage::Hints { move_number, hints }).await;
+            }
+            Err(e) => send_error(&state, conn_id, "katago_error", format!("Failed to get hints: {}", e)).await,
+        }
     });
 }
 
-/// Handle game reset
-async fn handle_reset(state: &AppState, board_size: usize) {
+/// Start the clock once both colors have a player, human or AI
+async fn start_clock_if_seated(state: &AppState, game: &Game) {
+    let ai_color = state.ai.lock().await.map(|seat| seat.color);
+    let connections = state.connections.lock().await;
+    let seated = |color| ai_color == Some(color) || connections.values().any(|conn| conn.color == Some(color));
+    if !(seated(Color::Black) && seated(Color::White)) {
+        return;
+    }
+    drop(connections);
+
+    if let Some(clock) = state.clock.lock().await.as_mut().filter(|clock| !clock.is_started()) {
+        clock.start(game, Instant::now());
+    }
+}
+
+/// Answer a hypothetical move for the asking connection only
+async fn handle_preview(state: &AppState, conn_id: u64, position: Position, color: Option<Color>) {
+    let own_color = state.connections.lock().await.get(&conn_id).and_then(|c| c.color);
+
+    let game = state.game.lock().await;
+    let color = color.or(own_color).unwrap_or(game.get_turn());
+    let result = game.preview_move(position, color);
+    drop(game);
+
+    let msg = match result {
+        Ok(preview) => ServerMessage::Preview {
+            position,
+            color,
+            legal: true,
+            reason: None,
+            board: Some(preview.board),
+            captures: preview.outcome.captures,
+            ko: preview.outcome.ko,
+        },
+        Err(e) => ServerMessage::Preview {
+            position,
+            color,
+            legal: false,
+            reason: Some(e.code()),
+            board: None,
+            captures: Vec::new(),
+            ko: None,
+        },
+    };
+    send_to(state, conn_id, &msg).await;
+}
+
+/// Handle resignation
+async fn handle_resign(state: &AppState, conn_id: u64) {
+    let Some(color) = player_color(state, conn_id).await else {
+        return;
+    };
+
+    // A flag that already fell decides the game; resigning afterwards is rejected
     let mut game = state.game.lock().await;
-    game.reset_with_size(board_size);
+    let timed_out = settle_clock(state, &mut game).await;
+    let result = game.resign(color);
     drop(game);
 
-    // Clear color assignments
+    match result {
+        Ok(()) => broadcast_state(state).await,
+        Err(e) => {
+            if timed_out {
+                broadcast_state(state).await;
+            }
+            send_move_error(state, conn_id, e).await;
+        }
+    }
+}
+
+/// Handle dead-stone marking, score acceptance and resumption
+async fn handle_scoring(state: &Arc<AppState>, conn_id: u64, action: ScoringAction) {
+    let Some(color) = player_color(state, conn_id).await else {
+        return;
+    };
+
+    let ai_color = state.ai.lock().await.map(|seat| seat.color);
+    let mut game = state.game.lock().await;
+    let result = apply_scoring(&mut game, color, action, ai_color);
+    settle_clock(state, &mut game).await; // Paused while scoring
+    drop(game);
+
+    match result {
+        Ok(()) => {
+            broadcast_state(state).await;
+            tokio::spawn(ai_turn(state.clone())); // Play may have resumed on the AI's turn
+        }
+        Err(e) => send_error(state, conn_id, "scoring_rejected", e).await,
+    }
+}
+
+/// Apply a player's scoring request; the AI seat goes along with whatever its opponent accepts
+fn apply_scoring(game: &mut Game, color: Color, action: ScoringAction, ai_color: Option<Color>) -> Result<(), String> {
+    match action {
+        ScoringAction::ToggleDead(pos) => game.toggle_dead(pos),
+        ScoringAction::Accept => {
+            game.accept_score(color)?;
+            match ai_color {
+                Some(ai_color) if ai_color != color => game.accept_score(ai_color),
+                _ => Ok(()),
+            }
+        }
+        ScoringAction::Resume => game.resume(color),
+    }
+}
+
+/// Handle game reset
+async fn handle_reset(
+    state: &AppState,
+    conn_id: u64,
+    board_size: BoardSize,
+    ruleset: Ruleset,
+    handicap: u8,
+    placement: HandicapPlacement,
+    settings: RoomSettings,
+) {
+    if let Some(Err(e)) = settings.time_control.as_ref().map(TimeControl::validate) {
+        send_error(state, conn_id, "invalid_time_control", e).await;
+        return;
+    }
+
+    let mut new_game = Game::with_size(board_size, ruleset);
+    if let Err(e) = new_game.setup_handicap(handicap, placement) {
+        send_error(state, conn_id, "invalid_handicap", e).await;
+        return;
+    }
+
+    *state.game.lock().await = new_game;
+    *state.clock.lock().await = settings.time_control.map(Clock::new);
+    *state.hints_enabled.lock().await = settings.hints;
+    *state.ai.lock().await = None;
+    state.evaluations.lock().await.clear();
+
+    // Clear color assignments (the AI seat included) and history browsing
     let mut connections = state.connections.lock().await;
     for conn in connections.values_mut() {
         conn.color = None;
+        conn.viewing = None;
     }
     drop(connections);
 
     broadcast_state(state).await;
 }
 
+/// Handle history browsing for a single connection (the live game is untouched)
+async fn handle_view(state: &AppState, conn_id: u64, navigation: Navigation) {
+    let game = state.game.lock().await;
+    let mut connections = state.connections.lock().await;
+
+    let Some(conn) = connections.get_mut(&conn_id) else {
+        return;
+    };
+
+    let total_moves = game.moves().len();
+    let current = conn.viewing.unwrap_or(total_moves).min(total_moves);
+    let target = match navigation {
+        Navigation::Goto(move_number) => move_number.min(total_moves),
+        Navigation::Back => current.saturating_sub(1),
+        Navigation::Forward => (current + 1).min(total_moves),
+        Navigation::Live => total_moves,
+    };
+
+    // Reaching the end of the record means following the live game again
+    let live = target == total_moves;
+    conn.viewing = if live { None } else { Some(target) };
+
+    let mut replay = game.replay();
+    replay.goto(target);
+
+    let msg = ServerMessage::HistoryView {
+        move_number: replay.move_number(),
+        total_moves,
+        live,
+        board: replay.board(),
+        last_move: replay.last_move().cloned(),
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    let _ = conn.sender.send(json);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ai_accepts_human_score() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        game.place_stone(Position::new(2, 2), Color::Black).unwrap();
+        game.pass(Color::White).unwrap();
+        game.toggle_dead(Position::new(2, 2)).unwrap_err(); // Still playing after one pass
+        game.pass(Color::Black).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Scoring);
+
+        // Marking alone does not end the game; accepting does, for both seats
+        apply_scoring(&mut game, Color::Black, ScoringAction::ToggleDead(Position::new(2, 2)), Some(Color::White)).unwrap();
+        apply_scoring(&mut game, Color::Black, ScoringAction::ToggleDead(Position::new(2, 2)), Some(Color::White)).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Scoring);
+        apply_scoring(&mut game, Color::Black, ScoringAction::Accept, Some(Color::White)).unwrap();
+
+        assert_eq!(game.get_phase(), GamePhase::Finished);
+        assert!(game.get_final_score().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_ai_move_dropped_after_reset() {
+        let seat = AiSeat { color: Color::Black, strength: AiStrength::default() };
+        let state = AppState::new();
+        *state.ai.lock().await = Some(seat);
+        let snapshot = state.game.lock().await.clone(); // Taken when the query went out
+
+        assert!(ai_move_wanted(&state, &snapshot, seat).await);
+
+        // Reset while KataGo is thinking: the seat is gone
+        *state.game.lock().await = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        *state.ai.lock().await = None;
+        assert!(!ai_move_wanted(&state, &snapshot, seat).await);
+
+        // Reseated on the new game: still not the position the answer is for
+        *state.ai.lock().await = Some(seat);
+        assert!(!ai_move_wanted(&state, &snapshot, seat).await);
+    }
+
+    #[tokio::test]
+    async fn test_resign_after_flag_fell() {
+        let state = AppState::new();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        state.connections.lock().await.insert(0, PlayerConnection { color: Some(Color::Black), sender, viewing: None });
+
+        // Black's clock has run out, but the tick has not noticed yet
+        let mut clock = Clock::new(TimeControl::Absolute { main_time: 0 });
+        clock.start(&*state.game.lock().await, Instant::now());
+        *state.clock.lock().await = Some(clock);
+
+        handle_resign(&state, 0).await;
+
+        assert_eq!(state.game.lock().await.get_result(), Some(GameResult::Time { winner: Color::White }));
+        let mut messages = Vec::new();
+        while let Ok(message) = receiver.try_recv() {
+            messages.push(message);
+        }
+        assert_eq!(messages.iter().filter(|m| m.contains("game_ended")).count(), 1);
+        assert!(messages.iter().any(|m| m.contains("\"code\":\"game_over\"")));
+    }
+
+    #[test]
+    fn test_same_position() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        let snapshot = game.clone();
+        assert!(same_position(&game, &snapshot));
+
+        game.pass(Color::Black).unwrap();
+        assert!(!same_position(&game, &snapshot));
+
+        let mut replaced = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        replaced.setup_handicap(2, HandicapPlacement::Fixed).unwrap();
+        assert!(!same_position(&replaced, &snapshot));
+    }
+
+    #[test]
+    fn test_humans_both_accept_score() {
+        let mut game = Game::with_size(BoardSize::square(9).unwrap(), Ruleset::default());
+        game.pass(Color::Black).unwrap();
+        game.pass(Color::White).unwrap();
+
+        apply_scoring(&mut game, Color::Black, ScoringAction::Accept, None).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Scoring);
+        apply_scoring(&mut game, Color::White, ScoringAction::Accept, None).unwrap();
+        assert_eq!(game.get_phase(), GamePhase::Finished);
+    }
+}